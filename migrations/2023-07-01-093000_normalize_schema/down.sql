@@ -0,0 +1,26 @@
+CREATE TABLE old_entries (
+    id INTEGER NOT NULL PRIMARY KEY,
+    club VARCHAR NOT NULL,
+    crew VARCHAR NOT NULL,
+    year INTEGER NOT NULL,
+    day INTEGER NOT NULL,
+    position INTEGER NOT NULL CHECK (position >= 0),
+    competition VARCHAR CHECK (competition IN ('early', 'mmays', 'wmays', 'mlents', 'wlents')) NOT NULL,
+    UNIQUE(crew,year,day,competition)
+);
+
+INSERT INTO old_entries (id, club, crew, year, day, position, competition)
+SELECT e.id, cl.name, c.alias, y.year, e.day, e.position, y.competition
+FROM entries e
+JOIN crews c ON c.id = e.crew_id
+JOIN clubs cl ON cl.id = c.club_id
+JOIN competition_years y ON y.id = e.competition_year_id;
+
+DROP TABLE entries;
+
+ALTER TABLE old_entries RENAME TO entries;
+
+DROP TABLE divisions;
+DROP TABLE competition_years;
+DROP TABLE crews;
+DROP TABLE clubs;
@@ -0,0 +1,62 @@
+CREATE TABLE clubs (
+    id INTEGER NOT NULL PRIMARY KEY,
+    name VARCHAR NOT NULL UNIQUE,
+    red INTEGER NOT NULL CHECK (red BETWEEN 0 AND 255),
+    green INTEGER NOT NULL CHECK (green BETWEEN 0 AND 255),
+    blue INTEGER NOT NULL CHECK (blue BETWEEN 0 AND 255)
+);
+
+CREATE TABLE crews (
+    id INTEGER NOT NULL PRIMARY KEY,
+    alias VARCHAR NOT NULL UNIQUE,
+    name VARCHAR NOT NULL,
+    club_id INTEGER NOT NULL REFERENCES clubs (id)
+);
+
+CREATE TABLE competition_years (
+    id INTEGER NOT NULL PRIMARY KEY,
+    competition VARCHAR CHECK (competition IN ('early', 'mmays', 'wmays', 'mlents', 'wlents')) NOT NULL,
+    year INTEGER NOT NULL,
+    days INTEGER NOT NULL CHECK (days >= 0),
+    UNIQUE(competition, year)
+);
+
+CREATE TABLE divisions (
+    competition_year_id INTEGER NOT NULL REFERENCES competition_years (id) ON DELETE CASCADE,
+    division INTEGER NOT NULL CHECK (division >= 1),
+    crews INTEGER NOT NULL CHECK (crews >= 0),
+    PRIMARY KEY (competition_year_id, division)
+);
+
+-- Carry over anything already imported. Club colours, crew names and
+-- division sizes weren't stored before, so they get placeholders until the
+-- next import.
+INSERT INTO clubs (name, red, green, blue)
+SELECT DISTINCT club, 0, 0, 0 FROM entries;
+
+INSERT INTO crews (alias, name, club_id)
+SELECT e.crew, e.crew, c.id
+FROM (SELECT crew, MIN(club) AS club FROM entries GROUP BY crew) e
+JOIN clubs c ON c.name = e.club;
+
+INSERT INTO competition_years (competition, year, days)
+SELECT competition, year, MAX(day) FROM entries GROUP BY competition, year;
+
+CREATE TABLE new_entries (
+    id INTEGER NOT NULL PRIMARY KEY,
+    crew_id INTEGER NOT NULL REFERENCES crews (id),
+    competition_year_id INTEGER NOT NULL REFERENCES competition_years (id) ON DELETE CASCADE,
+    day INTEGER NOT NULL CHECK (day >= 0),
+    position INTEGER NOT NULL CHECK (position >= 0),
+    UNIQUE(crew_id, competition_year_id, day)
+);
+
+INSERT INTO new_entries (id, crew_id, competition_year_id, day, position)
+SELECT e.id, c.id, y.id, e.day, e.position
+FROM entries e
+JOIN crews c ON c.alias = e.crew
+JOIN competition_years y ON y.competition = e.competition AND y.year = e.year;
+
+DROP TABLE entries;
+
+ALTER TABLE new_entries RENAME TO entries;
@@ -0,0 +1,321 @@
+// Backtesting for prediction models. For each year Y, a model sees only the
+// results from before Y, is given Y's real start order, and predicts how
+// likely each crew is to finish in each position. The predictions are then
+// scored against what actually happened:
+//
+// - log-loss: the average of -ln(chance given to the actual finish),
+// - Brier score: the average squared error of the chances, summed over
+//   positions, and
+// - position MAE: the average distance between the expected and actual
+//   finishing positions.
+//
+// Lower is better for all three.
+
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use crate::competition::Competition;
+use crate::dataset::Dataset;
+use crate::ratings;
+use crate::simulate::{self, Race};
+use crate::year::YearChart;
+
+// Chances are clamped to at least this, so that a confident wrong prediction
+// costs a lot rather than infinitely much.
+const MIN_PROBABILITY: f64 = 1e-6;
+
+pub trait Model {
+    fn name(&self) -> String;
+
+    /// For each crew in `start_order` (head first), the chance of it finishing
+    /// in each position, from 1. `history` only holds results from before
+    /// `year`.
+    fn predict(
+        &self,
+        history: &Dataset,
+        competition: Competition,
+        year: u32,
+        chart: &YearChart,
+        start_order: &[&str],
+    ) -> Vec<Vec<f64>>;
+}
+
+/// A baseline that predicts every crew finishes where it started.
+pub struct StayPut;
+
+impl Model for StayPut {
+    fn name(&self) -> String {
+        String::from("stay-put")
+    }
+
+    fn predict(
+        &self,
+        _history: &Dataset,
+        _competition: Competition,
+        _year: u32,
+        _chart: &YearChart,
+        start_order: &[&str],
+    ) -> Vec<Vec<f64>> {
+        (0..start_order.len())
+            .map(|i| {
+                let mut chances = vec![0.0; start_order.len()];
+                chances[i] = 1.0;
+                chances
+            })
+            .collect()
+    }
+}
+
+/// Ratings (see `ratings`) fitted on the history, fed into the Monte Carlo
+/// simulation (see `simulate`).
+pub struct Simulation {
+    pub ratings: ratings::Params,
+    pub simulate: simulate::Params,
+    pub runs: u32,
+    pub seed: u64,
+}
+
+impl Model for Simulation {
+    fn name(&self) -> String {
+        String::from("simulation")
+    }
+
+    fn predict(
+        &self,
+        history: &Dataset,
+        competition: Competition,
+        year: u32,
+        chart: &YearChart,
+        start_order: &[&str],
+    ) -> Vec<Vec<f64>> {
+        let fitted = ratings::fit(history, &self.ratings);
+        let strengths = ratings::strengths(&fitted, &self.ratings, competition, year);
+
+        let race = Race {
+            crews: start_order
+                .iter()
+                .map(|alias| {
+                    let strength = strengths.get(*alias).copied();
+                    (alias.to_string(), strength.unwrap_or(self.ratings.initial))
+                })
+                .collect(),
+            crews_per_division: chart.crews_per_division.clone(),
+            days: chart.days,
+        };
+
+        // Seeded per year, so that results don't depend on which years are
+        // backtested together.
+        let mut rng = StdRng::seed_from_u64(self.seed ^ year as u64);
+        let outcomes = simulate::simulate(&race, &self.simulate, self.runs, &mut rng);
+
+        (0..start_order.len())
+            .map(|i| {
+                (1..=start_order.len())
+                    .map(|position| outcomes.finish_probability(i, position as u8))
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Scores {
+    pub year: u32,
+    pub crews: usize,
+    pub log_loss: f64,
+    pub brier: f64,
+    pub position_mae: f64,
+}
+
+/// Score `predictions` (as returned by `Model::predict`) against the actual
+/// finishing positions, from 1.
+pub fn score(year: u32, predictions: &[Vec<f64>], finishes: &[u8]) -> Scores {
+    let mut log_loss = 0.0;
+    let mut brier = 0.0;
+    let mut position_mae = 0.0;
+
+    for (chances, finish) in predictions.iter().zip(finishes) {
+        let actual = *finish as usize - 1;
+
+        let chance = chances.get(actual).copied().unwrap_or(0.0);
+        log_loss -= chance.max(MIN_PROBABILITY).ln();
+
+        brier += chances
+            .iter()
+            .enumerate()
+            .map(|(i, p)| {
+                if i == actual {
+                    (1.0 - p).powi(2)
+                } else {
+                    p * p
+                }
+            })
+            .sum::<f64>();
+
+        if actual >= chances.len() {
+            brier += 1.0;
+        }
+
+        let expected: f64 = chances
+            .iter()
+            .enumerate()
+            .map(|(i, p)| (i + 1) as f64 * p)
+            .sum();
+        position_mae += (expected - *finish as f64).abs();
+    }
+
+    let crews = predictions.len().min(finishes.len());
+    let n = crews.max(1) as f64;
+
+    Scores {
+        year,
+        crews,
+        log_loss: log_loss / n,
+        brier: brier / n,
+        position_mae: position_mae / n,
+    }
+}
+
+/// Backtest `model` on each of `years` of `competition` that has results.
+pub fn backtest(
+    dataset: &Dataset,
+    model: &dyn Model,
+    competition: Competition,
+    years: impl IntoIterator<Item = u32>,
+) -> Vec<Scores> {
+    let mut scores = vec![];
+
+    for year in years {
+        let Some(chart) = dataset.chart(competition, year) else {
+            continue;
+        };
+
+        let crews = dataset.start_order(competition, year);
+
+        if crews.is_empty() {
+            continue;
+        }
+
+        let start_order: Vec<&str> = crews.iter().map(|crew| crew.alias.as_str()).collect();
+
+        // Every crew in the start order has a non-zero start position, so
+        // always has a finish.
+        let finishes: Vec<u8> = crews
+            .iter()
+            .filter_map(|crew| crew.year(year)?.iter().rev().copied().find(|x| *x != 0))
+            .collect();
+
+        let history = dataset.before(year);
+        let predictions = model.predict(&history, competition, year, chart, &start_order);
+
+        scores.push(score(year, &predictions, &finishes));
+    }
+
+    scores
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use super::{backtest, score, Model, StayPut};
+    use crate::competition::Competition;
+    use crate::dataset::Dataset;
+    use crate::source::testing::dataset;
+    use crate::year::YearChart;
+
+    const MAYS: Competition = Competition::MenMays;
+
+    fn close(x: f64, y: f64) -> bool {
+        (x - y).abs() < 1e-9
+    }
+
+    #[test]
+    fn perfect() {
+        let predictions = vec![
+            vec![0.0, 1.0, 0.0],
+            vec![1.0, 0.0, 0.0],
+            vec![0.0, 0.0, 1.0],
+        ];
+        let scores = score(2000, &predictions, &[2, 1, 3]);
+
+        assert_eq!(scores.crews, 3);
+        assert!(close(scores.log_loss, 0.0));
+        assert!(close(scores.brier, 0.0));
+        assert!(close(scores.position_mae, 0.0));
+    }
+
+    #[test]
+    fn uniform() {
+        let predictions = vec![vec![0.25; 4]; 4];
+        let scores = score(2000, &predictions, &[1, 2, 3, 4]);
+
+        assert!(close(scores.log_loss, 4f64.ln()));
+        // (1 - 1/4)^2 for the actual finish, plus (1/4)^2 for the other three.
+        assert!(close(scores.brier, 0.75));
+        // Every crew's expected finish is 2.5.
+        assert!(close(scores.position_mae, 1.0));
+    }
+
+    // Fails if it's shown the year it's predicting, or anything after it.
+    struct NoPeeking {
+        seen: RefCell<Vec<(u32, Vec<u32>)>>,
+    }
+
+    impl Model for NoPeeking {
+        fn name(&self) -> String {
+            String::from("no-peeking")
+        }
+
+        fn predict(
+            &self,
+            history: &Dataset,
+            competition: Competition,
+            year: u32,
+            chart: &YearChart,
+            start_order: &[&str],
+        ) -> Vec<Vec<f64>> {
+            let years: Vec<u32> = history.years(competition).collect();
+
+            assert!(history.chart(competition, year).is_none());
+            assert!(history
+                .crews_in(competition, year)
+                .all(|crew| crew.year(year).is_none()));
+
+            self.seen.borrow_mut().push((year, years));
+
+            StayPut.predict(history, competition, year, chart, start_order)
+        }
+    }
+
+    #[test]
+    fn trained_on_earlier_years_only() {
+        let dataset = dataset(
+            MAYS,
+            &[(2000, 1, &[2]), (2001, 1, &[2]), (2002, 1, &[2])],
+            &[
+                ("A", "a1", 2000, &[1, 1]),
+                ("B", "b1", 2000, &[2, 2]),
+                ("A", "a1", 2001, &[1, 2]),
+                ("B", "b1", 2001, &[2, 1]),
+                ("A", "a1", 2002, &[2, 2]),
+                ("B", "b1", 2002, &[1, 1]),
+            ],
+        );
+
+        let model = NoPeeking {
+            seen: RefCell::new(vec![]),
+        };
+        let scores = backtest(&dataset, &model, MAYS, 2000..=2002);
+
+        assert_eq!(
+            model.seen.into_inner(),
+            [(2000, vec![]), (2001, vec![2000]), (2002, vec![2000, 2001])]
+        );
+
+        // Staying put is right in 2000 and 2002, and wrong for both crews in
+        // 2001.
+        let maes: Vec<f64> = scores.iter().map(|x| x.position_mae).collect();
+        assert_eq!(maes, [0.0, 1.0, 0.0]);
+    }
+}
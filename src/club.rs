@@ -0,0 +1,147 @@
+// Club-level summaries, adding up the results of every boat a club entered.
+// Positions are finishing positions, so a club's highest boat is the one that
+// finished nearest the head.
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ClubYear<'a> {
+    pub year: u32,
+    pub boats: u32,
+    // Places gained (or lost, if negative) across every boat.
+    pub net_places: i32,
+    // The highest and lowest boats, with their finishing positions.
+    pub highest: (&'a str, u8),
+    pub lowest: (&'a str, u8),
+    // Boats entered compared with the previous year the competition was
+    // held, or `None` for the first year.
+    pub boats_change: Option<i32>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ClubDecade {
+    pub decade: u32, // e.g. 1990
+    pub years: u32,  // years in which the club entered at least one boat
+    pub boats: u32,  // boats entered, summed across those years
+    pub net_places: i32,
+    pub highest: u8,
+    pub lowest: u8,
+}
+
+impl ClubDecade {
+    pub fn boats_per_year(&self) -> f64 {
+        self.boats as f64 / self.years as f64
+    }
+}
+
+/// Group `years` (in order) into decades.
+pub fn decades(years: &[ClubYear]) -> Vec<ClubDecade> {
+    let mut decades: Vec<ClubDecade> = vec![];
+
+    for year in years {
+        let decade = year.year / 10 * 10;
+
+        match decades.last_mut() {
+            Some(last) if last.decade == decade => {
+                last.years += 1;
+                last.boats += year.boats;
+                last.net_places += year.net_places;
+                last.highest = last.highest.min(year.highest.1);
+                last.lowest = last.lowest.max(year.lowest.1);
+            }
+            _ => decades.push(ClubDecade {
+                decade,
+                years: 1,
+                boats: year.boats,
+                net_places: year.net_places,
+                highest: year.highest.1,
+                lowest: year.lowest.1,
+            }),
+        }
+    }
+
+    decades
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decades, ClubDecade};
+    use crate::competition::Competition;
+    use crate::source::testing::dataset;
+
+    const MAYS: Competition = Competition::MenMays;
+
+    #[test]
+    fn club_years() {
+        let dataset = dataset(
+            MAYS,
+            &[
+                (2000, 1, &[3]),
+                (2001, 1, &[3]),
+                (2002, 1, &[3]),
+                (2003, 1, &[3]),
+            ],
+            &[
+                ("A", "a1", 2000, &[1, 1]),
+                ("A", "a2", 2000, &[3, 2]),
+                ("B", "b1", 2000, &[2, 3]),
+                // a1 is overbumped, which costs three places.
+                ("A", "a1", 2001, &[1, 4]),
+                ("B", "b1", 2001, &[4, 1]),
+                ("B", "b1", 2002, &[1, 1]),
+                ("A", "a1", 2003, &[2, 2]),
+            ],
+        );
+
+        let years = dataset.club_years(MAYS, "A");
+        let summary: Vec<_> = years
+            .iter()
+            .map(|x| (x.year, x.boats, x.boats_change, x.net_places))
+            .collect();
+
+        // No boats in 2002, so no summary, but 2003's change is from 2002.
+        assert_eq!(
+            summary,
+            [
+                (2000, 2, None, 1),
+                (2001, 1, Some(-1), -3),
+                (2003, 1, Some(1), 0)
+            ]
+        );
+        assert_eq!((years[0].highest, years[0].lowest), (("a1", 1), ("a2", 2)));
+    }
+
+    #[test]
+    fn decade_boundaries() {
+        let dataset = dataset(
+            MAYS,
+            &[(1998, 1, &[2]), (1999, 1, &[2]), (2000, 1, &[2])],
+            &[
+                ("A", "a1", 1998, &[2, 1]),
+                ("A", "a2", 1998, &[1, 2]),
+                ("A", "a1", 1999, &[2, 1]),
+                ("A", "a1", 2000, &[1, 2]),
+            ],
+        );
+
+        assert_eq!(
+            decades(&dataset.club_years(MAYS, "A")),
+            [
+                ClubDecade {
+                    decade: 1990,
+                    years: 2,
+                    boats: 3,
+                    net_places: 1,
+                    highest: 1,
+                    lowest: 2,
+                },
+                ClubDecade {
+                    decade: 2000,
+                    years: 1,
+                    boats: 1,
+                    net_places: -1,
+                    highest: 2,
+                    lowest: 2,
+                },
+            ]
+        );
+    }
+}
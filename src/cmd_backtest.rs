@@ -0,0 +1,105 @@
+use std::error::Error;
+
+use clap::{Parser, ValueEnum};
+
+use the_bumps::backtest::{self, Model, Simulation, StayPut};
+use the_bumps::{ratings, simulate, Competition};
+
+use crate::source_args::SourceArgs;
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum ModelName {
+    /// Every crew finishes where it started.
+    StayPut,
+    /// Ratings fitted on earlier years, fed into the simulation.
+    Simulation,
+}
+
+#[derive(Parser, Debug)]
+pub(crate) struct Backtest {
+    #[arg(long, value_enum)]
+    competition: Competition,
+    #[arg(long)]
+    min_year: u32,
+    #[arg(long)]
+    max_year: u32,
+    /// The models to compare (default: all of them).
+    #[arg(long, value_enum)]
+    model: Vec<ModelName>,
+    /// Simulation runs per year.
+    #[arg(long, default_value_t = 2000)]
+    runs: u32,
+    #[arg(long, default_value_t = 0)]
+    seed: u64,
+    #[command(flatten)]
+    source: SourceArgs,
+}
+
+pub(crate) fn run(args: &Backtest) -> Result<(), Box<dyn Error>> {
+    // Every competition is loaded, since the ratings use them all.
+    let dataset = args
+        .source
+        .load(&Competition::all(), Some(0..=args.max_year))?;
+
+    let names = if args.model.is_empty() {
+        vec![ModelName::StayPut, ModelName::Simulation]
+    } else {
+        args.model.clone()
+    };
+
+    let models: Vec<Box<dyn Model>> = names
+        .iter()
+        .map(|name| -> Box<dyn Model> {
+            match name {
+                ModelName::StayPut => Box::new(StayPut),
+                ModelName::Simulation => Box::new(Simulation {
+                    ratings: ratings::Params::default(),
+                    simulate: simulate::Params::default(),
+                    runs: args.runs,
+                    seed: args.seed,
+                }),
+            }
+        })
+        .collect();
+
+    println!("{}", args.competition);
+
+    for model in &models {
+        let scores = backtest::backtest(
+            &dataset,
+            model.as_ref(),
+            args.competition,
+            args.min_year..=args.max_year,
+        );
+
+        println!("{}", model.name());
+        println!(
+            "{:<6} {:>5} {:>9} {:>7} {:>7}",
+            "year", "crews", "log-loss", "brier", "mae"
+        );
+
+        for x in &scores {
+            println!(
+                "{:<6} {:>5} {:>9.3} {:>7.3} {:>7.2}",
+                x.year, x.crews, x.log_loss, x.brier, x.position_mae
+            );
+        }
+
+        // Weighted by crews, so big years count for more.
+        let crews: usize = scores.iter().map(|x| x.crews).sum();
+        let mean = |f: fn(&backtest::Scores) -> f64| {
+            scores.iter().map(|x| f(x) * x.crews as f64).sum::<f64>() / crews.max(1) as f64
+        };
+
+        println!(
+            "{:<6} {:>5} {:>9.3} {:>7.3} {:>7.2}",
+            "all",
+            crews,
+            mean(|x| x.log_loss),
+            mean(|x| x.brier),
+            mean(|x| x.position_mae)
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,141 @@
+use std::{collections::BTreeMap, error::Error};
+
+use clap::{Parser, ValueEnum};
+
+use the_bumps::{Competition, CrewRecord};
+
+use crate::source_args::SourceArgs;
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum GroupBy {
+    Year,
+    Club,
+}
+
+#[derive(Parser, Debug)]
+pub(crate) struct Blades {
+    /// Only list these competitions (default: all of them).
+    #[arg(long, value_enum)]
+    competition: Vec<Competition>,
+    #[arg(long)]
+    min_year: Option<u32>,
+    #[arg(long)]
+    max_year: Option<u32>,
+    #[arg(long, value_enum, default_value = "year")]
+    by: GroupBy,
+    #[command(flatten)]
+    source: SourceArgs,
+}
+
+#[derive(Default)]
+struct Awards<'a> {
+    blades: Vec<(u32, &'a CrewRecord)>,
+    spoons: Vec<(u32, &'a CrewRecord)>,
+}
+
+fn print_line(label: &str, crews: &[String]) {
+    if !crews.is_empty() {
+        println!("  {label}: {}", crews.join(", "));
+    }
+}
+
+pub(crate) fn run(args: &Blades) -> Result<(), Box<dyn Error>> {
+    let competitions = if args.competition.is_empty() {
+        Competition::all().to_vec()
+    } else {
+        args.competition.clone()
+    };
+
+    let years = match (args.min_year, args.max_year) {
+        (None, None) => None,
+        (min, max) => Some(min.unwrap_or(0)..=max.unwrap_or(u32::MAX)),
+    };
+
+    let dataset = args.source.load(&competitions, years)?;
+
+    for competition in &competitions {
+        let mut awards = Awards::default();
+
+        for year in dataset.years(*competition) {
+            let Some(chart) = dataset.chart(*competition, year) else {
+                continue;
+            };
+
+            for crew in dataset.crews_in(*competition, year) {
+                if crew.blades(year, chart.days) {
+                    awards.blades.push((year, crew));
+                }
+
+                if crew.spoons(year, chart.days) {
+                    awards.spoons.push((year, crew));
+                }
+            }
+        }
+
+        println!("{competition}");
+
+        match args.by {
+            GroupBy::Year => {
+                for year in dataset.years(*competition) {
+                    let names = |list: &[(u32, &CrewRecord)]| -> Vec<String> {
+                        list.iter()
+                            .filter(|(y, _)| *y == year)
+                            .map(|(_, crew)| crew.name.clone())
+                            .collect()
+                    };
+
+                    let (blades, spoons) = (names(&awards.blades), names(&awards.spoons));
+
+                    if !blades.is_empty() || !spoons.is_empty() {
+                        println!("{year}");
+                        print_line("blades", &blades);
+                        print_line("spoons", &spoons);
+                    }
+                }
+            }
+            GroupBy::Club => {
+                for club in dataset.clubs().clubs() {
+                    let names = |list: &[(u32, &CrewRecord)]| -> Vec<String> {
+                        list.iter()
+                            .filter(|(_, crew)| club.crews.iter().any(|x| x.alias == crew.alias))
+                            .map(|(year, crew)| format!("{} ({year})", crew.name))
+                            .collect()
+                    };
+
+                    let (blades, spoons) = (names(&awards.blades), names(&awards.spoons));
+
+                    if !blades.is_empty() || !spoons.is_empty() {
+                        println!("{}", club.name);
+                        print_line("blades", &blades);
+                        print_line("spoons", &spoons);
+                    }
+                }
+            }
+        }
+
+        // Career totals, keyed by alias, most blades first.
+        let mut totals: BTreeMap<&str, (usize, usize)> = BTreeMap::new();
+
+        for crew in dataset.crews(*competition) {
+            let count = |list: &[(u32, &CrewRecord)]| {
+                list.iter().filter(|(_, x)| x.alias == crew.alias).count()
+            };
+            let (blades, spoons) = (count(&awards.blades), count(&awards.spoons));
+
+            if blades + spoons > 0 {
+                totals.insert(&crew.alias, (blades, spoons));
+            }
+        }
+
+        let mut totals: Vec<_> = totals.into_iter().collect();
+        totals.sort_by_key(|(_, (blades, spoons))| (std::cmp::Reverse(*blades), *spoons));
+
+        println!("totals");
+
+        for (alias, (blades, spoons)) in totals {
+            println!("  {alias}: {blades} blades, {spoons} spoons");
+        }
+    }
+
+    Ok(())
+}
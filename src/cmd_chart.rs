@@ -0,0 +1,36 @@
+use std::{error::Error, path::PathBuf};
+
+use clap::Parser;
+
+use the_bumps::{svg, Competition};
+
+use crate::source_args::SourceArgs;
+
+#[derive(Parser, Debug)]
+pub(crate) struct Chart {
+    #[arg(long, value_enum)]
+    competition: Competition,
+    #[arg(long)]
+    year: u32,
+    #[command(flatten)]
+    source: SourceArgs,
+    /// Where to write the SVG (default: standard output).
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+pub(crate) fn run(args: &Chart) -> Result<(), Box<dyn Error>> {
+    let dataset = args
+        .source
+        .load(&[args.competition], Some(args.year..=args.year))?;
+
+    let svg = svg::bumps_chart(&dataset, args.competition, args.year)
+        .ok_or_else(|| format!("No results for the {} in {}", args.competition, args.year))?;
+
+    match &args.output {
+        Some(output) => std::fs::write(output, svg)?,
+        None => print!("{svg}"),
+    }
+
+    Ok(())
+}
@@ -0,0 +1,97 @@
+use std::error::Error;
+
+use clap::Parser;
+
+use the_bumps::{club, Competition};
+
+use crate::source_args::SourceArgs;
+
+#[derive(Parser, Debug)]
+pub(crate) struct Club {
+    /// The club's name, as it appears in College.dat.
+    #[arg(long)]
+    club: String,
+    /// Only summarise these competitions (default: all of them).
+    #[arg(long, value_enum)]
+    competition: Vec<Competition>,
+    #[arg(long)]
+    min_year: Option<u32>,
+    #[arg(long)]
+    max_year: Option<u32>,
+    /// Summarise each decade rather than each year.
+    #[arg(long)]
+    decades: bool,
+    #[command(flatten)]
+    source: SourceArgs,
+}
+
+fn signed(n: i32) -> String {
+    format!("{n:+}")
+}
+
+pub(crate) fn run(args: &Club) -> Result<(), Box<dyn Error>> {
+    let competitions = if args.competition.is_empty() {
+        Competition::all().to_vec()
+    } else {
+        args.competition.clone()
+    };
+
+    // The year before --min-year is needed for the change in boats entered.
+    let years = match (args.min_year, args.max_year) {
+        (None, None) => None,
+        (min, max) => Some(min.unwrap_or(0).saturating_sub(1)..=max.unwrap_or(u32::MAX)),
+    };
+
+    let dataset = args.source.load(&competitions, years)?;
+
+    if !dataset.clubs().clubs().iter().any(|x| x.name == args.club) {
+        return Err(format!("No club called {:?}", args.club).into());
+    }
+
+    for competition in &competitions {
+        let mut years = dataset.club_years(*competition, &args.club);
+        years.retain(|x| args.min_year.is_none_or(|min| x.year >= min));
+
+        println!("{competition}: {}", args.club);
+
+        if args.decades {
+            println!(
+                "{:<8} {:>5} {:>11} {:>6} {:>7} {:>6}",
+                "decade", "years", "boats/year", "places", "highest", "lowest"
+            );
+
+            for decade in club::decades(&years) {
+                println!(
+                    "{:<8} {:>5} {:>11.1} {:>6} {:>7} {:>6}",
+                    format!("{}s", decade.decade),
+                    decade.years,
+                    decade.boats_per_year(),
+                    signed(decade.net_places),
+                    decade.highest,
+                    decade.lowest
+                );
+            }
+        } else {
+            println!(
+                "{:<6} {:>5} {:>6} {:>6}  {:<20} lowest",
+                "year", "boats", "change", "places", "highest"
+            );
+
+            for year in &years {
+                let highest = format!("{} ({})", year.highest.0, year.highest.1);
+
+                println!(
+                    "{:<6} {:>5} {:>6} {:>6}  {highest:<20} {} ({})",
+                    year.year,
+                    year.boats,
+                    year.boats_change.map_or_else(|| String::from("-"), signed),
+                    signed(year.net_places),
+                    year.lowest.0,
+                    year.lowest.1
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,113 @@
+use std::{error::Error, path::PathBuf};
+
+use clap::{ArgGroup, Parser};
+
+use the_bumps::{source, Competition};
+
+use crate::source_args::SourceArgs;
+
+/// Compare two sources, each either the CD-ROM files or a database, e.g. a
+/// database with the files it was built from, or two databases.
+#[derive(Parser, Debug)]
+#[command(group(ArgGroup::new("a").args(["a_data_dir", "a_db"]).required(true)))]
+#[command(group(ArgGroup::new("b").args(["b_data_dir", "b_db"]).required(true)))]
+pub(crate) struct CrossCheck {
+    /// Only check these competitions (default: all of them).
+    #[arg(long, value_enum)]
+    competition: Vec<Competition>,
+    #[arg(long)]
+    min_year: Option<u32>,
+    #[arg(long)]
+    max_year: Option<u32>,
+    // Not two flattened `SourceArgs`, whose --data-dir and --db would clash.
+    /// The CD-ROM files to compare.
+    #[arg(long)]
+    a_data_dir: Option<PathBuf>,
+    /// A database built by `db from-bumps-cd-rom` to compare.
+    #[arg(long)]
+    a_db: Option<PathBuf>,
+    /// The CD-ROM files to compare them with.
+    #[arg(long)]
+    b_data_dir: Option<PathBuf>,
+    /// A database to compare them with.
+    #[arg(long)]
+    b_db: Option<PathBuf>,
+}
+
+pub(crate) fn run(args: &CrossCheck) -> Result<(), Box<dyn Error>> {
+    let competitions = if args.competition.is_empty() {
+        Competition::all().to_vec()
+    } else {
+        args.competition.clone()
+    };
+
+    let years = match (args.min_year, args.max_year) {
+        (None, None) => None,
+        (min, max) => Some(min.unwrap_or(0)..=max.unwrap_or(u32::MAX)),
+    };
+
+    let a = SourceArgs::new(args.a_data_dir.clone(), args.a_db.clone());
+    let b = SourceArgs::new(args.b_data_dir.clone(), args.b_db.clone());
+
+    let differences = source::differences(
+        &a.load(&competitions, years.clone())?,
+        &b.load(&competitions, years)?,
+    );
+
+    for difference in &differences {
+        println!("{difference}");
+    }
+
+    if !differences.is_empty() {
+        return Err(format!(
+            "{} differences between {} and {}",
+            differences.len(),
+            a.path().display(),
+            b.path().display()
+        )
+        .into());
+    }
+
+    println!("{} and {} agree", a.path().display(), b.path().display());
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::CrossCheck;
+
+    #[test]
+    fn any_two_sources() {
+        for (a, b) in [
+            ("--a-data-dir", "--b-db"),
+            ("--a-db", "--b-db"),
+            ("--a-data-dir", "--b-data-dir"),
+        ] {
+            let args = CrossCheck::try_parse_from(["cross-check", a, "x", b, "y"]).unwrap();
+
+            assert!(args.a_data_dir.is_some() != args.a_db.is_some());
+            assert!(args.b_data_dir.is_some() != args.b_db.is_some());
+        }
+    }
+
+    #[test]
+    fn one_source_per_side() {
+        for args in [
+            vec!["cross-check", "--a-db", "x"],
+            vec![
+                "cross-check",
+                "--a-db",
+                "x",
+                "--a-data-dir",
+                "y",
+                "--b-db",
+                "z",
+            ],
+        ] {
+            assert!(CrossCheck::try_parse_from(args).is_err());
+        }
+    }
+}
@@ -1,7 +1,6 @@
-use std::collections::BTreeMap;
-use std::collections::HashMap;
-use std::convert::TryInto;
+use std::collections::{HashMap, HashSet};
 use std::error::Error;
+use std::ops::RangeInclusive;
 use std::path::Path;
 use std::path::PathBuf;
 
@@ -10,19 +9,30 @@ use diesel::insert_into;
 use diesel::prelude::*;
 use diesel::SqliteConnection;
 
-use crate::colleges;
-use crate::colleges::Clubs;
-use crate::crew::CrewRecord;
-use crate::db_entry::Competition;
-use crate::db_entry::NewEntry;
-use crate::decode;
-use crate::raw;
-use crate::rw2;
-use crate::year;
+use the_bumps::db::{self, Migrations};
+use the_bumps::db_entry::{Division, NewClub, NewCompetitionYear, NewCrew, NewEntry};
+use the_bumps::schema::{clubs, competition_years, crews, divisions, entries};
+use the_bumps::BumpsError;
+use the_bumps::Competition;
+use the_bumps::Dataset;
+
+// Rows per INSERT or DELETE, to stay well under SQLite's limit on bound
+// parameters.
+const BATCH_SIZE: usize = 1000;
 
 #[derive(Parser, Debug)]
 pub(crate) enum Subcommand {
     FromBumpsCdRom(BuildArgs),
+    /// Create the tables, or bring them up to date.
+    Migrate(DbArgs),
+    /// List the migrations, and whether each has been applied.
+    Status(DbArgs),
+}
+
+#[derive(Parser, Debug)]
+pub(crate) struct DbArgs {
+    #[arg(long, default_value = "bumps.db")]
+    sqlite_path: PathBuf,
 }
 
 #[derive(Parser, Debug)]
@@ -31,143 +41,753 @@ pub(crate) struct BuildArgs {
     data_dir: PathBuf,
     #[arg(long)]
     sqlite_path: Option<String>,
+    /// Only import these competitions (default: all of them).
+    #[arg(long, value_enum)]
+    competition: Vec<Competition>,
+    /// Only import these years, e.g. `1998` or `1990-1999`.
+    #[arg(long, value_parser = parse_years)]
+    years: Option<RangeInclusive<u32>>,
+    /// Work out what would change, but don't write anything.
+    #[arg(long)]
+    dry_run: bool,
 }
 
-fn load_clubs(data_dir: &Path) -> Result<Clubs, Box<dyn Error>> {
-    let colleges = data_dir.join("Data/College.dat");
+fn parse_years(value: &str) -> Result<RangeInclusive<u32>, String> {
+    let (min, max) = value.split_once('-').unwrap_or((value, value));
+    let year = |x: &str| {
+        x.trim()
+            .parse::<u32>()
+            .map_err(|err| format!("invalid year {x:?}: {err}"))
+    };
 
-    let mut colleges = decode::Decoder::new(std::fs::File::open(&colleges)?)?;
+    Ok(year(min)?..=year(max)?)
+}
 
-    colleges::Clubs::from_file(&mut colleges)
+#[derive(Debug, Default, Clone, Copy)]
+struct Changes {
+    inserted: usize,
+    updated: usize,
+    deleted: usize,
 }
 
-fn load_years(
-    data_dir: &Path,
-    clubs: &Clubs,
-    competition: Competition,
-) -> Result<HashMap<String, CrewRecord>, Box<dyn Error>> {
-    let raw = data_dir.join(format!("Data/{}.raw", competition.raw_name()));
+#[derive(Debug, Default)]
+struct Counts {
+    clubs: Changes,
+    crews: Changes,
+    competition_years: Changes,
+    divisions: Changes,
+    entries: Changes,
+}
+
+// Raised to roll back the transaction after a dry run.
+enum ImportError {
+    Db(diesel::result::Error),
+    DryRun(Counts),
+}
 
-    let rw2 = data_dir.join(format!("Data/{}.rw2", competition.raw_name()));
+impl From<diesel::result::Error> for ImportError {
+    fn from(err: diesel::result::Error) -> Self {
+        Self::Db(err)
+    }
+}
 
-    let crews = clubs.crews();
-    let rows = rw2::Row::from_file(&rw2)?;
-    let mut raw = raw::Raw::load(&raw)?;
+fn sync_clubs(conn: &mut SqliteConnection, dataset: &Dataset) -> QueryResult<Changes> {
+    let mut changes = Changes::default();
 
-    if rows.len() != crews.len() {
-        return Err(
-            "Invalid input data: number of crews does not match the number expected.".into(),
-        );
+    let existing: HashMap<String, (i32, i32, i32, i32)> = clubs::table
+        .select((
+            clubs::name,
+            (clubs::id, clubs::red, clubs::green, clubs::blue),
+        ))
+        .load(conn)?
+        .into_iter()
+        .collect();
+
+    let mut new_clubs = vec![];
+
+    for club in dataset.clubs().clubs() {
+        let (red, green, blue) = club.colour;
+        let colour = (red as i32, green as i32, blue as i32);
+
+        match existing.get(&club.name) {
+            None => new_clubs.push(NewClub {
+                name: &club.name,
+                red: colour.0,
+                green: colour.1,
+                blue: colour.2,
+            }),
+            Some((id, r, g, b)) if (*r, *g, *b) != colour => {
+                diesel::update(clubs::table.find(id))
+                    .set((
+                        clubs::red.eq(colour.0),
+                        clubs::green.eq(colour.1),
+                        clubs::blue.eq(colour.2),
+                    ))
+                    .execute(conn)?;
+                changes.updated += 1;
+            }
+            Some(_) => {}
+        }
     }
 
-    let min_year = rows
-        .iter()
-        .filter_map(|x| x.start_year())
-        .min()
-        .ok_or("Unable to find the minimum year.")?;
-    let max_year = rows
+    changes.inserted = insert_into(clubs::table).values(&new_clubs).execute(conn)?;
+
+    Ok(changes)
+}
+
+fn sync_crews(conn: &mut SqliteConnection, dataset: &Dataset) -> QueryResult<Changes> {
+    let mut changes = Changes::default();
+
+    let club_ids: HashMap<String, i32> = clubs::table
+        .select((clubs::name, clubs::id))
+        .load(conn)?
+        .into_iter()
+        .collect();
+
+    let existing: HashMap<String, (i32, String, i32)> = crews::table
+        .select((crews::alias, (crews::id, crews::name, crews::club_id)))
+        .load(conn)?
+        .into_iter()
+        .collect();
+
+    let mut new_crews = vec![];
+
+    for club in dataset.clubs().clubs() {
+        let club_id = club_ids[&club.name];
+
+        for crew in &club.crews {
+            match existing.get(&crew.alias) {
+                None => new_crews.push(NewCrew {
+                    alias: &crew.alias,
+                    name: &crew.name,
+                    club_id,
+                }),
+                Some((id, name, old_club_id)) if *name != crew.name || *old_club_id != club_id => {
+                    diesel::update(crews::table.find(id))
+                        .set((crews::name.eq(&crew.name), crews::club_id.eq(club_id)))
+                        .execute(conn)?;
+                    changes.updated += 1;
+                }
+                Some(_) => {}
+            }
+        }
+    }
+
+    changes.inserted = insert_into(crews::table).values(&new_crews).execute(conn)?;
+
+    Ok(changes)
+}
+
+// Delete the crews no longer in College.dat, and then the clubs, unless
+// they're still needed by results outside the competitions and years being
+// imported. This runs after the entries are brought up to date.
+fn prune_crews(conn: &mut SqliteConnection, dataset: &Dataset) -> QueryResult<usize> {
+    let wanted: HashSet<&str> = dataset
+        .clubs()
+        .crews()
+        .into_iter()
+        .map(|crew| crew.alias.as_str())
+        .collect();
+
+    let in_use: HashSet<i32> = entries::table
+        .select(entries::crew_id)
+        .distinct()
+        .load::<i32>(conn)?
+        .into_iter()
+        .collect();
+
+    let stale: Vec<i32> = crews::table
+        .select((crews::id, crews::alias))
+        .load::<(i32, String)>(conn)?
+        .into_iter()
+        .filter(|(id, alias)| !wanted.contains(alias.as_str()) && !in_use.contains(id))
+        .map(|(id, _)| id)
+        .collect();
+
+    let mut deleted = 0;
+
+    for batch in stale.chunks(BATCH_SIZE) {
+        deleted += diesel::delete(crews::table.filter(crews::id.eq_any(batch))).execute(conn)?;
+    }
+
+    Ok(deleted)
+}
+
+fn prune_clubs(conn: &mut SqliteConnection, dataset: &Dataset) -> QueryResult<usize> {
+    let wanted: HashSet<&str> = dataset
+        .clubs()
+        .clubs()
         .iter()
-        .filter_map(|x| x.end_year())
-        .max()
-        .ok_or("Unable to find the maximum year.")?;
+        .map(|club| club.name.as_str())
+        .collect();
+
+    let in_use: HashSet<i32> = crews::table
+        .select(crews::club_id)
+        .distinct()
+        .load::<i32>(conn)?
+        .into_iter()
+        .collect();
 
-    let years = {
-        let years: Result<HashMap<u32, year::Year>, Box<dyn std::error::Error>> = (min_year
-            ..=max_year)
-            .map(|year| {
-                let path =
-                    data_dir.join(format!("Charts/{}/{}.dat", competition.charts_name(), year));
+    let stale: Vec<i32> = clubs::table
+        .select((clubs::id, clubs::name))
+        .load::<(i32, String)>(conn)?
+        .into_iter()
+        .filter(|(id, name)| !wanted.contains(name.as_str()) && !in_use.contains(id))
+        .map(|(id, _)| id)
+        .collect();
+
+    let mut deleted = 0;
 
-                let mut reader = decode::Decoder::new(std::fs::File::open(&path)?)?;
+    for batch in stale.chunks(BATCH_SIZE) {
+        deleted += diesel::delete(clubs::table.filter(clubs::id.eq_any(batch))).execute(conn)?;
+    }
 
-                let info = year::Year::from_file(&mut reader)?;
+    Ok(deleted)
+}
 
-                Ok((year, info))
-            })
-            .collect();
-        years?
+fn import(
+    conn: &mut SqliteConnection,
+    dataset: &Dataset,
+    competitions: &[Competition],
+    years: &RangeInclusive<u32>,
+) -> QueryResult<Counts> {
+    let mut counts = Counts {
+        clubs: sync_clubs(conn, dataset)?,
+        crews: sync_crews(conn, dataset)?,
+        ..Counts::default()
     };
 
-    Ok(rows
+    let crew_ids: HashMap<String, i32> = crews::table
+        .select((crews::alias, crews::id))
+        .load(conn)?
         .into_iter()
-        .zip(crews)
-        .filter_map(|(row, crew)| {
-            let years: BTreeMap<u32, Vec<u8>> = (row.start_year()?..=row.end_year()?)
-                .scan(row.start_idx()?, |start_idx, year| {
-                    let this_start_idx = *start_idx;
-                    let next_start_idx = this_start_idx + (years[&year].days as u32) + 1;
-                    *start_idx = next_start_idx;
-
-                    assert!((next_start_idx - 1) <= row.end_idx().unwrap());
-
-                    let positions = raw.range(this_start_idx, next_start_idx).unwrap();
-
-                    if positions.iter().all(|x| (*x == 0)) {
-                        Some(None)
-                    } else {
-                        Some(Some((year, positions)))
-                    }
-                })
-                .flatten()
-                .collect();
-
-            Some((
-                crew.alias.clone(),
-                CrewRecord::new(crew.name.clone(), crew.alias.clone(), years),
-            ))
-        })
-        .collect())
+        .collect();
+
+    // Only competition-years within the filters are touched.
+    let min_year = (*years.start()).min(i32::MAX as u32) as i32;
+    let max_year = (*years.end()).min(i32::MAX as u32) as i32;
+
+    let in_scope = || {
+        competition_years::table
+            .filter(competition_years::competition.eq_any(competitions.to_vec()))
+            .filter(competition_years::year.between(min_year, max_year))
+    };
+
+    let existing_years: HashMap<(Competition, i32), (i32, i32)> = in_scope()
+        .select((
+            competition_years::competition,
+            competition_years::year,
+            competition_years::id,
+            competition_years::days,
+        ))
+        .load::<(Competition, i32, i32, i32)>(conn)?
+        .into_iter()
+        .map(|(competition, year, id, days)| ((competition, year), (id, days)))
+        .collect();
+
+    let mut wanted_years = HashMap::new();
+
+    for competition in competitions {
+        for year in dataset.years(*competition) {
+            if let Some(chart) = dataset.chart(*competition, year) {
+                wanted_years.insert((*competition, year as i32), (year, chart));
+            }
+        }
+    }
+
+    let mut new_years = vec![];
+
+    for (key, (_, chart)) in &wanted_years {
+        let days = chart.days as i32;
+
+        match existing_years.get(key) {
+            None => new_years.push(NewCompetitionYear {
+                competition: key.0,
+                year: key.1,
+                days,
+            }),
+            Some((id, old_days)) if *old_days != days => {
+                diesel::update(competition_years::table.find(id))
+                    .set(competition_years::days.eq(days))
+                    .execute(conn)?;
+                counts.competition_years.updated += 1;
+            }
+            Some(_) => {}
+        }
+    }
+
+    counts.competition_years.inserted = insert_into(competition_years::table)
+        .values(&new_years)
+        .execute(conn)?;
+
+    let stale_years: Vec<i32> = existing_years
+        .iter()
+        .filter(|(key, _)| !wanted_years.contains_key(key))
+        .map(|(_, (id, _))| *id)
+        .collect();
+
+    for batch in stale_years.chunks(BATCH_SIZE) {
+        counts.divisions.deleted +=
+            diesel::delete(divisions::table.filter(divisions::competition_year_id.eq_any(batch)))
+                .execute(conn)?;
+        counts.entries.deleted +=
+            diesel::delete(entries::table.filter(entries::competition_year_id.eq_any(batch)))
+                .execute(conn)?;
+        counts.competition_years.deleted +=
+            diesel::delete(competition_years::table.filter(competition_years::id.eq_any(batch)))
+                .execute(conn)?;
+    }
+
+    let year_ids: HashMap<(Competition, i32), i32> = in_scope()
+        .select((
+            competition_years::competition,
+            competition_years::year,
+            competition_years::id,
+        ))
+        .load::<(Competition, i32, i32)>(conn)?
+        .into_iter()
+        .map(|(competition, year, id)| ((competition, year), id))
+        .collect();
+
+    let scope_ids: Vec<i32> = year_ids.values().copied().collect();
+
+    // Divisions, keyed by (competition-year, division).
+    let mut existing_divisions: HashMap<(i32, i32), i32> = HashMap::new();
+    // Entries, keyed by (crew, competition-year, day), with their id and
+    // position.
+    let mut existing_entries: HashMap<(i32, i32, i32), (i32, i32)> = HashMap::new();
+
+    for batch in scope_ids.chunks(BATCH_SIZE) {
+        existing_divisions.extend(
+            divisions::table
+                .filter(divisions::competition_year_id.eq_any(batch))
+                .select((
+                    divisions::competition_year_id,
+                    divisions::division,
+                    divisions::crews,
+                ))
+                .load::<(i32, i32, i32)>(conn)?
+                .into_iter()
+                .map(|(year_id, division, crews)| ((year_id, division), crews)),
+        );
+
+        existing_entries.extend(
+            entries::table
+                .filter(entries::competition_year_id.eq_any(batch))
+                .select((
+                    entries::crew_id,
+                    entries::competition_year_id,
+                    entries::day,
+                    entries::id,
+                    entries::position,
+                ))
+                .load::<(i32, i32, i32, i32, i32)>(conn)?
+                .into_iter()
+                .map(|(crew, year_id, day, id, position)| ((crew, year_id, day), (id, position))),
+        );
+    }
+
+    let mut wanted_divisions = HashMap::new();
+    let mut wanted_entries = HashMap::new();
+
+    for ((competition, _), (year, chart)) in &wanted_years {
+        let year_id = year_ids[&(*competition, *year as i32)];
+
+        for (i, size) in chart.crews_per_division.iter().enumerate() {
+            wanted_divisions.insert((year_id, i as i32 + 1), *size as i32);
+        }
+
+        for crew in dataset.crews_in(*competition, *year) {
+            let crew_id = crew_ids[&crew.alias];
+
+            for (day, position) in crew.year(*year).into_iter().flatten().enumerate() {
+                wanted_entries.insert((crew_id, year_id, day as i32), *position as i32);
+            }
+        }
+    }
+
+    let mut new_divisions = vec![];
+
+    for ((year_id, division), crews) in &wanted_divisions {
+        match existing_divisions.get(&(*year_id, *division)) {
+            None => new_divisions.push(Division {
+                competition_year_id: *year_id,
+                division: *division,
+                crews: *crews,
+            }),
+            Some(old) if old != crews => {
+                diesel::update(divisions::table.find((year_id, division)))
+                    .set(divisions::crews.eq(crews))
+                    .execute(conn)?;
+                counts.divisions.updated += 1;
+            }
+            Some(_) => {}
+        }
+    }
+
+    for batch in new_divisions.chunks(BATCH_SIZE) {
+        counts.divisions.inserted += insert_into(divisions::table).values(batch).execute(conn)?;
+    }
+
+    for (year_id, division) in existing_divisions.keys() {
+        if !wanted_divisions.contains_key(&(*year_id, *division)) {
+            counts.divisions.deleted +=
+                diesel::delete(divisions::table.find((year_id, division))).execute(conn)?;
+        }
+    }
+
+    let mut new_entries = vec![];
+
+    for ((crew_id, year_id, day), position) in &wanted_entries {
+        match existing_entries.get(&(*crew_id, *year_id, *day)) {
+            None => new_entries.push(NewEntry {
+                crew_id: *crew_id,
+                competition_year_id: *year_id,
+                day: *day,
+                position: *position,
+            }),
+            Some((id, old)) if old != position => {
+                diesel::update(entries::table.find(id))
+                    .set(entries::position.eq(position))
+                    .execute(conn)?;
+                counts.entries.updated += 1;
+            }
+            Some(_) => {}
+        }
+    }
+
+    for batch in new_entries.chunks(BATCH_SIZE) {
+        counts.entries.inserted += insert_into(entries::table).values(batch).execute(conn)?;
+    }
+
+    let stale_entries: Vec<i32> = existing_entries
+        .iter()
+        .filter(|(key, _)| !wanted_entries.contains_key(key))
+        .map(|(_, (id, _))| *id)
+        .collect();
+
+    for batch in stale_entries.chunks(BATCH_SIZE) {
+        counts.entries.deleted +=
+            diesel::delete(entries::table.filter(entries::id.eq_any(batch))).execute(conn)?;
+    }
+
+    counts.crews.deleted = prune_crews(conn, dataset)?;
+    counts.clubs.deleted = prune_clubs(conn, dataset)?;
+
+    Ok(counts)
 }
 
 fn from_bumps_cdrom(args: &BuildArgs) -> Result<(), Box<dyn std::error::Error>> {
     let sqlite_path = args.sqlite_path.clone().unwrap_or("bumps.db".into());
+    let path = Path::new(&sqlite_path);
 
-    let mut conn = SqliteConnection::establish(&sqlite_path)?;
+    // A dry run mustn't change the schema either.
+    let migrations = if args.dry_run {
+        Migrations::Check
+    } else {
+        Migrations::Apply
+    };
 
-    let clubs = load_clubs(&args.data_dir)?;
+    let mut conn = db::connect(path, migrations)?;
 
-    let mut all_entries = vec![];
+    let competitions = if args.competition.is_empty() {
+        Competition::all().to_vec()
+    } else {
+        args.competition.clone()
+    };
 
-    for comp in [
-        Competition::Early,
-        Competition::MenLents,
-        Competition::MenMays,
-        Competition::WomenLents,
-        Competition::WomenMays,
-    ] {
-        let crews = load_years(&args.data_dir, &clubs, comp).unwrap();
-
-        for this_club in clubs.clubs() {
-            for this_crew in &this_club.crews {
-                if let Some(crew_record) = crews.get(&this_crew.alias) {
-                    for (y, days) in &crew_record.years {
-                        for (day_number, pos) in days.iter().enumerate() {
-                            all_entries.push(NewEntry {
-                                year: *y as i32,
-                                day: day_number.try_into().unwrap(),
-                                club: &this_club.name,
-                                crew: &this_crew.alias,
-                                competition: comp,
-                                position: *pos as i32,
-                            })
-                        }
-                    }
-                }
-            }
+    let years = args.years.clone().unwrap_or(0..=u32::MAX);
+    let dataset = Dataset::load(&args.data_dir, &competitions, args.years.clone())?;
+
+    // Everything happens in one transaction, so a failed import (or a dry
+    // run) leaves the database as it was.
+    let result = conn.transaction(|conn| {
+        let counts = import(conn, &dataset, &competitions, &years)?;
+
+        if args.dry_run {
+            Err(ImportError::DryRun(counts))
+        } else {
+            Ok(counts)
         }
+    });
+
+    let counts = match result {
+        Ok(counts) => counts,
+        Err(ImportError::DryRun(counts)) => counts,
+        Err(ImportError::Db(err)) => return Err(BumpsError::db(path)(err).into()),
+    };
+
+    println!(
+        "{:<18} {:>8} {:>8} {:>8}",
+        "", "inserted", "updated", "deleted"
+    );
+
+    for (table, changes) in [
+        ("clubs", counts.clubs),
+        ("crews", counts.crews),
+        ("competition_years", counts.competition_years),
+        ("divisions", counts.divisions),
+        ("entries", counts.entries),
+    ] {
+        println!(
+            "{table:<18} {:>8} {:>8} {:>8}",
+            changes.inserted, changes.updated, changes.deleted
+        );
     }
 
-    println!("inserting {} entries", all_entries.len());
+    if args.dry_run {
+        println!("dry run: nothing was written");
+    }
+
+    Ok(())
+}
 
-    insert_into(crate::schema::entries::table)
-        .values(&all_entries)
-        .execute(&mut conn)?;
+fn migrate(args: &DbArgs) -> Result<(), Box<dyn Error>> {
+    let mut conn = db::open(&args.sqlite_path)?;
+    let applied = db::migrate(&mut conn, &args.sqlite_path)?;
+
+    if applied.is_empty() {
+        println!("already up to date");
+    }
+
+    for version in applied {
+        println!("applied {version}");
+    }
+
+    Ok(())
+}
+
+fn status(args: &DbArgs) -> Result<(), Box<dyn Error>> {
+    let mut conn = db::open_existing(&args.sqlite_path)?;
+
+    for (migration, applied) in db::status(&mut conn, &args.sqlite_path)? {
+        let state = if applied { "applied" } else { "pending" };
+        println!("{state:<8} {migration}");
+    }
 
     Ok(())
 }
 
 pub(crate) fn run(command: &Subcommand) -> Result<(), Box<dyn Error>> {
     match command {
-        Subcommand::FromBumpsCdRom(args) => from_bumps_cdrom(&args),
+        Subcommand::FromBumpsCdRom(args) => from_bumps_cdrom(args),
+        Subcommand::Migrate(args) => migrate(args),
+        Subcommand::Status(args) => status(args),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::ops::RangeInclusive;
+    use std::path::Path;
+
+    use diesel::prelude::*;
+    use diesel::SqliteConnection;
+
+    use the_bumps::colleges::{Club, Clubs, Crew};
+    use the_bumps::crew::CrewRecord;
+    use the_bumps::db::{self, DbSource, Migrations};
+    use the_bumps::schema::{competition_years, crews, entries};
+    use the_bumps::source::{self, DataSource};
+    use the_bumps::year::YearChart;
+    use the_bumps::{BumpsError, Competition, Dataset};
+
+    use super::{import, parse_years, Changes, Counts};
+
+    const MAYS: Competition = Competition::MenMays;
+
+    // Clubs, by name, with the aliases of their crews.
+    struct Results(Vec<(&'static str, Vec<&'static str>)>);
+
+    // Two crews, rowing in 2000 and 2001, and two more that never raced.
+    fn results() -> Results {
+        Results(vec![
+            ("Club", vec!["a1", "a2", "a3"]),
+            ("Other", vec!["b1"]),
+        ])
+    }
+
+    impl DataSource for Results {
+        fn clubs(&mut self) -> Result<Clubs, BumpsError> {
+            let crew = |alias: &&str| Crew {
+                name: alias.to_string(),
+                alias: alias.to_string(),
+            };
+
+            Ok(Clubs::new(
+                self.0
+                    .iter()
+                    .map(|(name, crews)| Club {
+                        name: name.to_string(),
+                        colour: (1, 2, 3),
+                        crews: crews.iter().map(crew).collect(),
+                    })
+                    .collect(),
+            ))
+        }
+
+        fn competition_years(
+            &mut self,
+            _competition: Competition,
+            years: Option<&RangeInclusive<u32>>,
+        ) -> Result<BTreeMap<u32, YearChart>, BumpsError> {
+            Ok((2000..=2001)
+                .filter(|x| years.is_none_or(|years| years.contains(x)))
+                .map(|year| (year, YearChart::new(2, vec![2])))
+                .collect())
+        }
+
+        fn positions(
+            &mut self,
+            _competition: Competition,
+            years: Option<&RangeInclusive<u32>>,
+        ) -> Result<Vec<CrewRecord>, BumpsError> {
+            let record = |alias: &str, results: [(u32, Vec<u8>); 2]| {
+                let results = IntoIterator::into_iter(results)
+                    .filter(|(x, _)| years.is_none_or(|years| years.contains(x)))
+                    .collect();
+
+                CrewRecord::new(alias.to_string(), alias.to_string(), results)
+            };
+
+            let listed: Vec<&str> = self.0.iter().flat_map(|(_, crews)| crews.clone()).collect();
+
+            Ok(vec![
+                record("a1", [(2000, vec![1, 2, 2]), (2001, vec![2, 2, 1])]),
+                record("a2", [(2000, vec![2, 1, 1]), (2001, vec![1, 1, 2])]),
+            ]
+            .into_iter()
+            .filter(|crew| listed.contains(&crew.alias.as_str()))
+            .collect())
+        }
+    }
+
+    fn connect() -> SqliteConnection {
+        db::connect(Path::new(":memory:"), Migrations::Apply).unwrap()
+    }
+
+    fn dataset(years: Option<RangeInclusive<u32>>) -> Dataset {
+        Dataset::from_source(&mut results(), &[MAYS], years).unwrap()
+    }
+
+    fn changes(counts: &Counts) -> [(usize, usize, usize); 5] {
+        [
+            counts.clubs,
+            counts.crews,
+            counts.competition_years,
+            counts.divisions,
+            counts.entries,
+        ]
+        .map(|x: Changes| (x.inserted, x.updated, x.deleted))
+    }
+
+    #[test]
+    fn years() {
+        assert_eq!(parse_years("1998"), Ok(1998..=1998));
+        assert_eq!(parse_years("1990-1999"), Ok(1990..=1999));
+        assert!(parse_years("1990-").is_err());
+        assert!(parse_years("nineteen").is_err());
+    }
+
+    #[test]
+    fn reimport_changes_nothing() {
+        let mut conn = connect();
+        let dataset = dataset(None);
+
+        let first = import(&mut conn, &dataset, &[MAYS], &(0..=u32::MAX)).unwrap();
+        assert_eq!(
+            changes(&first),
+            [(2, 0, 0), (4, 0, 0), (2, 0, 0), (2, 0, 0), (12, 0, 0)]
+        );
+
+        let second = import(&mut conn, &dataset, &[MAYS], &(0..=u32::MAX)).unwrap();
+        assert_eq!(changes(&second), [(0, 0, 0); 5]);
+    }
+
+    #[test]
+    fn years_filter_leaves_other_years_alone() {
+        let mut conn = connect();
+        import(&mut conn, &dataset(None), &[MAYS], &(0..=u32::MAX)).unwrap();
+
+        // Importing only 2001 from data that has lost 2000 mustn't delete 2000.
+        let only_2001 = dataset(Some(2001..=2001));
+        let counts = import(&mut conn, &only_2001, &[MAYS], &(2001..=2001)).unwrap();
+        assert_eq!(changes(&counts), [(0, 0, 0); 5]);
+
+        let years: Vec<i32> = competition_years::table
+            .select(competition_years::year)
+            .order(competition_years::year)
+            .load(&mut conn)
+            .unwrap();
+        assert_eq!(years, [2000, 2001]);
+
+        let entries: i64 = entries::table.count().get_result(&mut conn).unwrap();
+        assert_eq!(entries, 12);
+    }
+
+    #[test]
+    fn years_filter_drops_stale_years_in_range() {
+        let mut conn = connect();
+        import(&mut conn, &dataset(None), &[MAYS], &(0..=u32::MAX)).unwrap();
+
+        // Re-importing 2000-2001 from data with only 2001 removes 2000.
+        let only_2001 = dataset(Some(2001..=2001));
+        let counts = import(&mut conn, &only_2001, &[MAYS], &(2000..=2001)).unwrap();
+        assert_eq!(
+            changes(&counts),
+            [(0, 0, 0), (0, 0, 0), (0, 0, 1), (0, 0, 1), (0, 0, 6)]
+        );
+    }
+
+    #[test]
+    fn reads_back_what_was_imported() {
+        let mut conn = connect();
+        import(&mut conn, &dataset(None), &[MAYS], &(0..=u32::MAX)).unwrap();
+
+        let mut db = DbSource::new(conn, Path::new(":memory:"));
+
+        for years in [None, Some(2001..=2001)] {
+            let expected = dataset(years.clone());
+            let actual = Dataset::from_source(&mut db, &[MAYS], years).unwrap();
+
+            assert_eq!(actual.clubs(), expected.clubs());
+            assert_eq!(
+                source::differences(&actual, &expected),
+                Vec::<String>::new()
+            );
+        }
+
+        let a1 = db.crew(MAYS, "a1", Some(&(2001..=2001))).unwrap().unwrap();
+        assert_eq!(a1.years, BTreeMap::from([(2001, vec![2, 2, 1])]));
+
+        let a3 = db.crew(MAYS, "a3", None).unwrap().unwrap();
+        assert!(a3.years.is_empty());
+
+        assert!(db.crew(MAYS, "z1", None).unwrap().is_none());
+    }
+
+    #[test]
+    fn removed_crews_and_clubs_are_deleted() {
+        let mut conn = connect();
+        import(&mut conn, &dataset(None), &[MAYS], &(0..=u32::MAX)).unwrap();
+
+        // Only a2 is left in College.dat, but a1 still has results in 2000,
+        // which isn't being imported.
+        let mut fewer = Results(vec![("Club", vec!["a2"])]);
+        let fewer = Dataset::from_source(&mut fewer, &[MAYS], Some(2001..=2001)).unwrap();
+        let counts = import(&mut conn, &fewer, &[MAYS], &(2001..=2001)).unwrap();
+
+        assert_eq!(
+            changes(&counts),
+            [(0, 0, 1), (0, 0, 2), (0, 0, 0), (0, 0, 0), (0, 0, 3)]
+        );
+
+        let aliases: Vec<String> = crews::table
+            .select(crews::alias)
+            .order(crews::alias)
+            .load(&mut conn)
+            .unwrap();
+        assert_eq!(aliases, ["a1", "a2"]);
     }
 }
@@ -0,0 +1,102 @@
+use std::error::Error;
+
+use clap::Parser;
+
+use the_bumps::headships::{self, Gained};
+use the_bumps::Competition;
+
+use crate::source_args::SourceArgs;
+
+#[derive(Parser, Debug)]
+pub(crate) struct Headships {
+    /// Only list these competitions (default: all of them).
+    #[arg(long, value_enum)]
+    competition: Vec<Competition>,
+    #[arg(long)]
+    min_year: Option<u32>,
+    #[arg(long)]
+    max_year: Option<u32>,
+    /// Only list the head of the river, not the head of every division.
+    #[arg(long)]
+    river_only: bool,
+    #[command(flatten)]
+    source: SourceArgs,
+}
+
+fn ordinal(n: u32) -> String {
+    let suffix = match (n % 10, n % 100) {
+        (_, 11..=13) => "th",
+        (1, _) => "st",
+        (2, _) => "nd",
+        (3, _) => "rd",
+        _ => "th",
+    };
+
+    format!("{n}{suffix}")
+}
+
+pub(crate) fn run(args: &Headships) -> Result<(), Box<dyn Error>> {
+    let competitions = if args.competition.is_empty() {
+        Competition::all().to_vec()
+    } else {
+        args.competition.clone()
+    };
+
+    // Every year is loaded, even outside --min-year/--max-year, so that
+    // reigns are counted in full.
+    let dataset = args.source.load(&competitions, None)?;
+
+    let in_range = |year: u32| {
+        args.min_year.is_none_or(|min| year >= min) && args.max_year.is_none_or(|max| year <= max)
+    };
+
+    for competition in &competitions {
+        let name = |alias: &str| {
+            dataset
+                .crew(*competition, alias)
+                .map_or_else(|| alias.to_string(), |crew| crew.name.clone())
+        };
+
+        println!("{competition}");
+
+        let mut last_year = None;
+
+        for headship in headships::headships(&dataset, *competition) {
+            if !in_range(headship.year) || (args.river_only && headship.division != 1) {
+                continue;
+            }
+
+            if last_year != Some(headship.year) {
+                println!("{}", headship.year);
+                last_year = Some(headship.year);
+            }
+
+            let title = match headship.division {
+                1 => String::from("head of the river"),
+                division => format!("head of division {division}"),
+            };
+
+            let how = match headship.gained {
+                Gained::Won {
+                    day,
+                    from: Some(from),
+                } => format!("won on day {day} from {}", name(from)),
+                Gained::Won { day, from: None } => format!("won on day {day}"),
+                Gained::Dropped { day, by: Some(by) } => {
+                    format!("dropped in on day {day}, bumped by {}", name(by))
+                }
+                Gained::Dropped { day, by: None } => format!("dropped in on day {day}"),
+                Gained::Held => String::from("held all week"),
+            };
+
+            println!(
+                "  {title}: {}, {how} ({} year of {})",
+                name(headship.crew),
+                ordinal(headship.streak),
+                headship.reign
+            );
+        }
+    }
+
+    Ok(())
+}
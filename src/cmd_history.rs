@@ -0,0 +1,82 @@
+use std::{error::Error, path::PathBuf};
+
+use clap::{Parser, ValueEnum};
+
+use the_bumps::{svg, Competition};
+
+use crate::source_args::SourceArgs;
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum Format {
+    Svg,
+    Html,
+}
+
+#[derive(Parser, Debug)]
+pub(crate) struct History {
+    /// One or more competitions, e.g. `--competition men-lents --competition
+    /// men-mays` to overlay the Lents and Mays.
+    #[arg(long, value_enum, required = true)]
+    competition: Vec<Competition>,
+    /// Crew aliases to draw.
+    #[arg(long)]
+    crew: Vec<String>,
+    /// Draw every crew belonging to these clubs.
+    #[arg(long)]
+    club: Vec<String>,
+    #[arg(long)]
+    min_year: u32,
+    #[arg(long)]
+    max_year: u32,
+    #[command(flatten)]
+    source: SourceArgs,
+    #[arg(long, value_enum, default_value = "svg")]
+    format: Format,
+    /// Where to write the chart (default: standard output).
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+pub(crate) fn run(args: &History) -> Result<(), Box<dyn Error>> {
+    let dataset = args
+        .source
+        .load(&args.competition, Some(args.min_year..=args.max_year))?;
+
+    let mut series = vec![];
+
+    for competition in &args.competition {
+        for alias in &args.crew {
+            series.push((*competition, alias.as_str()));
+        }
+
+        for club in &args.club {
+            for crew in dataset.club_crews(*competition, club) {
+                if !crew.years.is_empty() {
+                    series.push((*competition, crew.alias.as_str()));
+                }
+            }
+        }
+    }
+
+    if series.is_empty() {
+        return Err("Nothing to draw: pass at least one --crew or --club".into());
+    }
+
+    let chart = svg::history_chart(&dataset, &series, args.min_year..=args.max_year)
+        .ok_or("None of those crews competed in those years")?;
+
+    let chart = match args.format {
+        Format::Svg => chart,
+        Format::Html => svg::html_page(
+            &format!("Bumps history {}-{}", args.min_year, args.max_year),
+            &chart,
+        ),
+    };
+
+    match &args.output {
+        Some(output) => std::fs::write(output, chart)?,
+        None => print!("{chart}"),
+    }
+
+    Ok(())
+}
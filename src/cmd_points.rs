@@ -0,0 +1,128 @@
+use std::error::Error;
+
+use clap::{Parser, ValueEnum};
+
+use the_bumps::points::{self, Preset, Scoring};
+use the_bumps::Competition;
+
+use crate::source_args::SourceArgs;
+
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq)]
+enum Series {
+    Mays,
+    Lents,
+}
+
+impl Series {
+    // Men's and women's crews score for the same club table.
+    fn competitions(&self) -> [Competition; 2] {
+        match self {
+            Self::Mays => [Competition::MenMays, Competition::WomenMays],
+            Self::Lents => [Competition::MenLents, Competition::WomenLents],
+        }
+    }
+}
+
+#[derive(Parser, Debug)]
+pub(crate) struct Points {
+    /// Only score these (default: the Mays and the Lents).
+    #[arg(long, value_enum)]
+    series: Vec<Series>,
+    #[arg(long)]
+    min_year: u32,
+    #[arg(long)]
+    max_year: u32,
+    /// The scoring rules to start from.
+    #[arg(long, value_enum, default_value = "places")]
+    rules: Preset,
+    /// Override the points for a bump.
+    #[arg(long, allow_negative_numbers = true)]
+    bump: Option<f64>,
+    /// Override the points for an overbump.
+    #[arg(long, allow_negative_numbers = true)]
+    overbump: Option<f64>,
+    /// Override the points for a double overbump.
+    #[arg(long, allow_negative_numbers = true)]
+    double_overbump: Option<f64>,
+    /// Override the points for being bumped.
+    #[arg(long, allow_negative_numbers = true)]
+    bumped: Option<f64>,
+    /// Override the points for being overbumped.
+    #[arg(long, allow_negative_numbers = true)]
+    overbumped: Option<f64>,
+    /// Override the points for being double overbumped.
+    #[arg(long, allow_negative_numbers = true)]
+    double_overbumped: Option<f64>,
+    /// Override the points for rowing over.
+    #[arg(long, allow_negative_numbers = true)]
+    row_over: Option<f64>,
+    /// Override the points for rowing over at the head.
+    #[arg(long, allow_negative_numbers = true)]
+    head_row_over: Option<f64>,
+    /// Rank clubs by total points, rather than points per boat.
+    #[arg(long)]
+    no_normalise: bool,
+    #[command(flatten)]
+    source: SourceArgs,
+}
+
+impl Points {
+    fn scoring(&self) -> Scoring {
+        let preset = Scoring::preset(self.rules);
+
+        Scoring {
+            bump: self.bump.unwrap_or(preset.bump),
+            overbump: self.overbump.unwrap_or(preset.overbump),
+            double_overbump: self.double_overbump.unwrap_or(preset.double_overbump),
+            bumped: self.bumped.unwrap_or(preset.bumped),
+            overbumped: self.overbumped.unwrap_or(preset.overbumped),
+            double_overbumped: self.double_overbumped.unwrap_or(preset.double_overbumped),
+            row_over: self.row_over.unwrap_or(preset.row_over),
+            head_row_over: self.head_row_over.unwrap_or(preset.head_row_over),
+            normalise: preset.normalise && !self.no_normalise,
+        }
+    }
+}
+
+pub(crate) fn run(args: &Points) -> Result<(), Box<dyn Error>> {
+    let series = if args.series.is_empty() {
+        vec![Series::Mays, Series::Lents]
+    } else {
+        args.series.clone()
+    };
+
+    let competitions: Vec<Competition> = series.iter().flat_map(|x| x.competitions()).collect();
+
+    let dataset = args
+        .source
+        .load(&competitions, Some(args.min_year..=args.max_year))?;
+
+    let scoring = args.scoring();
+
+    for series in &series {
+        let competitions = series.competitions();
+
+        for year in args.min_year..=args.max_year {
+            let table = points::points_table(&dataset, &competitions, year, &scoring);
+
+            if table.is_empty() {
+                continue;
+            }
+
+            println!("{series:?} {year}");
+
+            for (rank, club) in table.iter().enumerate() {
+                println!(
+                    "{:>3}. {:<24} {:>3} boats {:>+7.1} points {:>+7.2}",
+                    rank + 1,
+                    club.club,
+                    club.boats,
+                    club.points,
+                    club.score
+                );
+            }
+        }
+    }
+
+    Ok(())
+}
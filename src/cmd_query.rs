@@ -1,11 +1,49 @@
-use std::{error::Error, collections::{HashMap, BTreeMap}, path::PathBuf};
+use std::{
+    error::Error,
+    io::{IsTerminal, Write},
+    path::PathBuf,
+    process::{Command, Stdio},
+};
 
 use clap::Parser;
 
-use crate::{db_entry::Competition, decode, colleges, rw2, raw, crew, year};
+use the_bumps::{events, terminal, Competition};
+
+use crate::source_args::SourceArgs;
 
 #[derive(Parser, Debug)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 pub(crate) struct Query {
+    #[command(subcommand)]
+    command: Option<QueryCommand>,
+    #[command(flatten)]
+    crew: Option<CrewQuery>,
+}
+
+#[derive(Parser, Debug)]
+enum QueryCommand {
+    /// Draw the bumps chart for a year in the terminal.
+    Chart(ChartQuery),
+}
+
+#[derive(Parser, Debug)]
+struct ChartQuery {
+    #[arg(long, value_enum)]
+    competition: Competition,
+    #[arg(long)]
+    year: u32,
+    #[command(flatten)]
+    source: SourceArgs,
+    /// Crew aliases or club names to pick out.
+    #[arg(long)]
+    highlight: Vec<String>,
+    /// Show the chart in $PAGER (or `less -R`).
+    #[arg(long)]
+    pager: bool,
+}
+
+#[derive(Parser, Debug)]
+struct CrewQuery {
     #[arg(long, value_enum)]
     competition: Competition,
     #[arg(long)]
@@ -14,105 +52,117 @@ pub(crate) struct Query {
     max_year: u16,
     #[arg(long)]
     crew: String,
+    // Not a flattened `SourceArgs`: clap can't tell whether an optional
+    // flattened group (like this one) was given if it has its own flattened
+    // groups.
+    #[arg(long, required_unless_present = "db", conflicts_with = "db")]
+    data_dir: Option<PathBuf>,
+    /// Read the results from a database built by `db from-bumps-cd-rom`,
+    /// rather than the CD-ROM files.
     #[arg(long)]
-    data_dir: PathBuf,
+    db: Option<PathBuf>,
 }
 
-pub(crate) fn run(query : &Query) -> Result<(), Box<dyn Error>> {
-    let raw = query
-        .data_dir
-        .join(format!("Data/{}.raw", query.competition.raw_name()));
-
-    let rw2 = query
-        .data_dir
-        .join(format!("Data/{}.rw2", query.competition.raw_name()));
-
-    let colleges = query.data_dir.join("Data/College.dat");
+pub(crate) fn run(query: &Query) -> Result<(), Box<dyn Error>> {
+    match (&query.command, &query.crew) {
+        (Some(QueryCommand::Chart(chart)), _) => run_chart(chart),
+        (None, Some(crew)) => run_crew(crew),
+        (None, None) => Err("Nothing to query: pass a crew, or use `query chart`".into()),
+    }
+}
 
-    let mut colleges = decode::Decoder::new(std::fs::File::open(&colleges)?)?;
+fn run_chart(query: &ChartQuery) -> Result<(), Box<dyn Error>> {
+    let dataset = query
+        .source
+        .load(&[query.competition], Some(query.year..=query.year))?;
+
+    // Colours are only worth drawing if they'll end up on a terminal that can
+    // show them.
+    let truecolor = std::io::stdout().is_terminal()
+        && matches!(
+            std::env::var("COLORTERM").as_deref(),
+            Ok("truecolor") | Ok("24bit")
+        );
+
+    let options = terminal::Options {
+        truecolor,
+        highlight: query.highlight.clone(),
+    };
 
-    let clubs = colleges::Clubs::from_file(&mut colleges)?;
-    let crews = clubs.crews();
-    let rows = rw2::Row::from_file(&rw2)?;
-    let mut raw = raw::Raw::load(&raw)?;
+    let chart = terminal::bumps_chart(&dataset, query.competition, query.year, &options)
+        .ok_or_else(|| format!("No results for the {} in {}", query.competition, query.year))?;
 
-    if rows.len() != crews.len() {
-        return Err(Box::new(std::io::Error::new(
-            std::io::ErrorKind::Other,
-            "Invalid input data: number of crews does not match the number expected.",
-        )));
+    if !query.pager {
+        print!("{chart}");
+        return Ok(());
     }
 
-    let min_year = rows.iter().filter_map(|x| x.start_year()).min();
-    let max_year = rows.iter().filter_map(|x| x.end_year()).max();
-
-    let years = {
-        match (min_year, max_year) {
-            (Some(min_year), Some(max_year)) => {
-                let years: Result<HashMap<u32, year::Year>, Box<dyn std::error::Error>> = (min_year
-                    ..=max_year)
-                    .map(|year| {
-                        let path = query.data_dir.join(format!(
-                            "Charts/{}/{}.dat",
-                            query.competition.charts_name(),
-                            year
-                        ));
-
-                        let mut reader = decode::Decoder::new(std::fs::File::open(&path)?)?;
-
-                        let info = year::Year::from_file(&mut reader)?;
-
-                        Ok((year, info))
-                    })
-                    .collect();
-                years?
-            }
-
-            _ => HashMap::new(),
-        }
-    };
+    let pager = std::env::var("PAGER").unwrap_or_else(|_| String::from("less -R"));
+    let mut child = Command::new("sh")
+        .arg("-c")
+        .arg(&pager)
+        .stdin(Stdio::piped())
+        .spawn()?;
 
-    let crews: HashMap<String, crew::CrewRecord> = rows
-        .into_iter()
-        .zip(crews)
-        .filter_map(|(row, crew)| {
-            let years: BTreeMap<u32, Vec<u8>> = (row.start_year()?..=row.end_year()?)
-                .scan(row.start_idx()?, |start_idx, year| {
-                    let this_start_idx = *start_idx;
-                    let next_start_idx = this_start_idx + (years[&year].days as u32) + 1;
-                    *start_idx = next_start_idx;
-
-                    assert!((next_start_idx - 1) <= row.end_idx().unwrap());
+    if let Some(mut stdin) = child.stdin.take() {
+        // The user quitting the pager early closes the pipe, which is fine.
+        let _ = stdin.write_all(chart.as_bytes());
+    }
 
-                    let positions = raw.range(this_start_idx, next_start_idx).unwrap();
+    child.wait()?;
 
-                    if positions.iter().all(|x| (*x == 0)) {
-                        Some(None)
-                    } else {
-                        Some(Some((year, positions)))
-                    }
-                })
-                .flatten()
-                .collect();
+    Ok(())
+}
 
-            Some((
-                crew.alias.clone(),
-                crew::CrewRecord::new(crew.name.clone(), crew.alias.clone(), years),
-            ))
-        })
-        .collect();
+fn run_crew(query: &CrewQuery) -> Result<(), Box<dyn Error>> {
+    let years = (query.min_year as u32)..=(query.max_year as u32);
 
-    let crew = &crews.get(&query.crew);
+    // Only the crew, and one year at a time, are read from the source, so
+    // that a database can answer with a few small queries.
+    let mut source = SourceArgs::new(query.data_dir.clone(), query.db.clone()).open()?;
+    let crew = source.crew(query.competition, &query.crew, Some(&years))?;
 
     match crew {
-        None => Err(Box::new(std::io::Error::new(
-            std::io::ErrorKind::Other,
-            format!("Crew {} not found", query.crew),
-        ))),
+        None => Err(Box::new(std::io::Error::other(format!(
+            "Crew {} not found",
+            query.crew
+        )))),
         Some(crew) => {
-            for year in query.min_year..=query.max_year {
-                match crew.year(year as u32) {
-                    Some(results) => println!("{year}: {:?}", results),
+            for year in years {
+                match crew.year(year) {
+                    Some(results) => {
+                        let one_year = year..=year;
+                        let divisions = source
+                            .competition_years(query.competition, Some(&one_year))?
+                            .get(&year)
+                            .map(|x| x.divisions());
+
+                        let place = |position: Option<&u8>| {
+                            let place = divisions.as_ref()?.locate(*position?)?;
+                            let sandwich = if place.sandwich { " (sandwich)" } else { "" };
+
+                            Some(format!(
+                                "division {}, place {}{sandwich}",
+                                place.division, place.place
+                            ))
+                        };
+
+                        match (place(results.first()), place(results.last())) {
+                            (Some(start), Some(finish)) => {
+                                println!(
+                                    "{year}: {:?} (started {start}, finished {finish})",
+                                    results
+                                )
+                            }
+                            _ => println!("{year}: {:?}", results),
+                        }
+
+                        let crews = source.positions(query.competition, Some(&one_year))?;
+
+                        for event in events::Events::infer(&crews, year).crew(&crew.alias) {
+                            println!("  {event}");
+                        }
+                    }
                     None => println!("results: (did not compete)"),
                 }
             }
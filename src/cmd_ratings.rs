@@ -0,0 +1,106 @@
+use std::error::Error;
+
+use clap::Parser;
+
+use the_bumps::ratings::{self, Params};
+use the_bumps::Competition;
+
+use crate::source_args::SourceArgs;
+
+#[derive(Parser, Debug)]
+pub(crate) struct Ratings {
+    /// Only rate these competitions (default: all of them).
+    #[arg(long, value_enum)]
+    competition: Vec<Competition>,
+    /// Show the table of ratings for this year.
+    #[arg(long, required_unless_present = "crew")]
+    year: Option<u32>,
+    /// Show how these crews' ratings changed over time.
+    #[arg(long)]
+    crew: Vec<String>,
+    #[arg(long, default_value_t = Params::default().k)]
+    k: f64,
+    #[arg(long, default_value_t = Params::default().season_decay)]
+    season_decay: f64,
+    #[arg(long, default_value_t = Params::default().year_decay)]
+    year_decay: f64,
+    #[command(flatten)]
+    source: SourceArgs,
+}
+
+pub(crate) fn run(args: &Ratings) -> Result<(), Box<dyn Error>> {
+    let competitions = if args.competition.is_empty() {
+        Competition::all().to_vec()
+    } else {
+        args.competition.clone()
+    };
+
+    // Ratings depend on every earlier year, so everything is loaded.
+    let dataset = args.source.load(&competitions, None)?;
+
+    let params = Params {
+        k: args.k,
+        season_decay: args.season_decay,
+        year_decay: args.year_decay,
+        ..Params::default()
+    };
+
+    let ratings = ratings::fit(&dataset, &params);
+
+    if let Some(year) = args.year {
+        for competition in &competitions {
+            let mut table: Vec<_> = ratings
+                .iter()
+                .filter(|x| x.competition == *competition && x.year == year)
+                .collect();
+
+            if table.is_empty() {
+                continue;
+            }
+
+            table.sort_by(|a, b| b.after.total_cmp(&a.after));
+
+            println!("{competition} {year}");
+            println!(
+                "{:>4} {:<12} {:>5} {:>6} {:>7} {:>7} {:>7}",
+                "rank", "crew", "start", "finish", "before", "after", "change"
+            );
+
+            for (rank, rating) in table.iter().enumerate() {
+                println!(
+                    "{:>4} {:<12} {:>5} {:>6} {:>7.0} {:>7.0} {:>+7.0}",
+                    rank + 1,
+                    rating.alias,
+                    rating.start,
+                    rating.finish,
+                    rating.before,
+                    rating.after,
+                    rating.change()
+                );
+            }
+        }
+    }
+
+    for alias in &args.crew {
+        println!("{alias}");
+        println!(
+            "{:<4} {:<24} {:>5} {:>6} {:>7} {:>7} {:>7}",
+            "year", "competition", "start", "finish", "before", "after", "change"
+        );
+
+        for rating in ratings.iter().filter(|x| x.alias == *alias) {
+            println!(
+                "{:<4} {:<24} {:>5} {:>6} {:>7.0} {:>7.0} {:>+7.0}",
+                rating.year,
+                rating.competition.to_string(),
+                rating.start,
+                rating.finish,
+                rating.before,
+                rating.after,
+                rating.change()
+            );
+        }
+    }
+
+    Ok(())
+}
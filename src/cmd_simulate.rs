@@ -0,0 +1,248 @@
+use std::{
+    error::Error,
+    path::{Path, PathBuf},
+};
+
+use clap::{ArgGroup, Parser};
+use rand::rngs::StdRng;
+use rand::SeedableRng;
+
+use the_bumps::ratings;
+use the_bumps::simulate::{self, Params, Race};
+use the_bumps::Competition;
+
+use crate::source_args::SourceArgs;
+
+#[derive(Parser, Debug)]
+#[command(group(ArgGroup::new("source").args(["data_dir", "db"])))]
+pub(crate) struct Simulate {
+    /// A file listing the start order, one crew alias per line, head first,
+    /// each optionally followed by its strength (default 1500).
+    #[arg(long, conflicts_with_all = ["competition", "year", "source"], requires_all = ["divs", "days"])]
+    start_order: Option<PathBuf>,
+    /// Division sizes, head first, for use with --start-order.
+    #[arg(long, value_delimiter = ',')]
+    divs: Vec<u8>,
+    /// The number of days, for use with --start-order.
+    #[arg(long)]
+    days: Option<u8>,
+    /// Simulate a year from the data files instead, using its real start
+    /// order and strengths rated on the years before.
+    #[arg(long, value_enum, requires_all = ["year", "source"])]
+    competition: Option<Competition>,
+    #[arg(long)]
+    year: Option<u32>,
+    // Not a flattened `SourceArgs`, whose --data-dir is required unless --db
+    // is given, even when --start-order means neither is needed.
+    #[arg(long, conflicts_with = "db")]
+    data_dir: Option<PathBuf>,
+    /// Read the results from a database built by `db from-bumps-cd-rom`,
+    /// rather than the CD-ROM files.
+    #[arg(long)]
+    db: Option<PathBuf>,
+    #[arg(long, default_value_t = 10000)]
+    runs: u32,
+    #[arg(long, default_value_t = Params::default().bump_rate)]
+    bump_rate: f64,
+    /// Seed the random number generator, for repeatable results.
+    #[arg(long)]
+    seed: Option<u64>,
+}
+
+fn read_start_order(path: &Path) -> Result<Vec<(String, f64)>, Box<dyn Error>> {
+    let initial = ratings::Params::default().initial;
+    let contents = std::fs::read_to_string(path)?;
+    let mut crews = vec![];
+
+    for (i, line) in contents.lines().enumerate() {
+        let mut fields = line.split_whitespace();
+
+        let Some(alias) = fields.next() else {
+            continue;
+        };
+
+        let strength = match fields.next() {
+            Some(x) => x
+                .parse()
+                .map_err(|_| format!("{}: line {}: bad strength {x:?}", path.display(), i + 1))?,
+            None => initial,
+        };
+
+        crews.push((String::from(alias), strength));
+    }
+
+    Ok(crews)
+}
+
+fn load_race(
+    source: &SourceArgs,
+    competition: Competition,
+    year: u32,
+) -> Result<Race, Box<dyn Error>> {
+    let dataset = source.load(&Competition::all(), Some(0..=year))?;
+
+    let chart = dataset
+        .chart(competition, year)
+        .ok_or_else(|| format!("No results for the {competition} in {year}"))?;
+
+    let params = ratings::Params::default();
+    let fitted = ratings::fit(&dataset, &params);
+    let strengths = ratings::strengths(&fitted, &params, competition, year);
+
+    let crews = dataset
+        .start_order(competition, year)
+        .into_iter()
+        .map(|crew| {
+            let strength = strengths
+                .get(&crew.alias)
+                .copied()
+                .unwrap_or(params.initial);
+
+            (crew.alias.clone(), strength)
+        })
+        .collect();
+
+    Ok(Race {
+        crews,
+        crews_per_division: chart.crews_per_division.clone(),
+        days: chart.days,
+    })
+}
+
+pub(crate) fn run(args: &Simulate) -> Result<(), Box<dyn Error>> {
+    let race = match (&args.start_order, args.competition, args.year) {
+        (Some(path), _, _) => Race {
+            crews: read_start_order(path)?,
+            crews_per_division: args.divs.clone(),
+            days: args.days.unwrap_or(4),
+        },
+        (None, Some(competition), Some(year)) => {
+            let source = SourceArgs::new(args.data_dir.clone(), args.db.clone());
+            load_race(&source, competition, year)?
+        }
+        _ => return Err("Pass either --start-order or --competition and --year".into()),
+    };
+
+    let mut rng = match args.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+
+    let params = Params {
+        bump_rate: args.bump_rate,
+    };
+
+    let outcomes = simulate::simulate(&race, &params, args.runs, &mut rng);
+
+    println!(
+        "{:>5} {:<12} {:>8} {:>6} {:>6}  finishing positions",
+        "start", "crew", "expected", "blades", "spoons"
+    );
+
+    for (i, crew) in outcomes.crews.iter().enumerate() {
+        let positions: Vec<String> = (1..=outcomes.crews.len() as u8)
+            .filter_map(|position| {
+                let p = outcomes.finish_probability(i, position);
+                (p >= 0.01).then(|| format!("{position}: {:.0}%", p * 100.0))
+            })
+            .collect();
+
+        println!(
+            "{:>5} {:<12} {:>8.1} {:>5.1}% {:>5.1}%  {}",
+            i + 1,
+            crew,
+            outcomes.expected_finish(i),
+            outcomes.blades_probability(i) * 100.0,
+            outcomes.spoons_probability(i) * 100.0,
+            positions.join(", ")
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::Parser;
+
+    use super::Simulate;
+
+    #[test]
+    fn start_order_needs_no_data() {
+        let args = Simulate::try_parse_from([
+            "simulate",
+            "--start-order",
+            "so.txt",
+            "--divs",
+            "3,2",
+            "--days",
+            "4",
+        ])
+        .unwrap();
+
+        assert_eq!(args.divs, vec![3, 2]);
+        assert_eq!(args.days, Some(4));
+        assert!(args.data_dir.is_none() && args.db.is_none());
+    }
+
+    #[test]
+    fn start_order_conflicts_with_data() {
+        let args = Simulate::try_parse_from([
+            "simulate",
+            "--start-order",
+            "so.txt",
+            "--divs",
+            "3,2",
+            "--days",
+            "4",
+            "--db",
+            "bumps.db",
+        ]);
+
+        assert!(args.is_err());
+    }
+
+    #[test]
+    fn competition_reads_from_either_source() {
+        for source in ["--data-dir", "--db"] {
+            let args = Simulate::try_parse_from([
+                "simulate",
+                "--competition",
+                "men-mays",
+                "--year",
+                "1998",
+                source,
+                "x",
+            ])
+            .unwrap();
+
+            assert_eq!(args.year, Some(1998));
+            assert!(args.data_dir.is_some() != args.db.is_some());
+        }
+    }
+
+    #[test]
+    fn competition_needs_a_source() {
+        let args =
+            Simulate::try_parse_from(["simulate", "--competition", "men-mays", "--year", "1998"]);
+
+        assert!(args.is_err());
+    }
+
+    #[test]
+    fn one_source_at_a_time() {
+        let args = Simulate::try_parse_from([
+            "simulate",
+            "--competition",
+            "men-mays",
+            "--year",
+            "1998",
+            "--data-dir",
+            "x",
+            "--db",
+            "y",
+        ]);
+
+        assert!(args.is_err());
+    }
+}
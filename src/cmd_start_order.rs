@@ -0,0 +1,232 @@
+use std::{
+    error::Error,
+    fmt::Write as _,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use clap::{Parser, ValueEnum};
+
+use the_bumps::decode;
+use the_bumps::start_order::{self, Rules, StartOrder};
+use the_bumps::year::{DAYS, DIVS};
+use the_bumps::Competition;
+
+use crate::source_args::SourceArgs;
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum Format {
+    Text,
+    Csv,
+    /// An encrypted chart file, with the DAYS and DIVS headers followed by
+    /// the start order, one alias per line.
+    Dat,
+}
+
+#[derive(Parser, Debug)]
+pub(crate) struct StartOrderArgs {
+    #[arg(long, value_enum)]
+    competition: Competition,
+    /// The year whose final positions to start from.
+    #[arg(long)]
+    year: u32,
+    /// Crews (by alias) not returning next year.
+    #[arg(long, value_delimiter = ',')]
+    leaving: Vec<String>,
+    /// A file listing the crews getting on, one alias per line, in the order
+    /// they qualified.
+    #[arg(long)]
+    getting_on: Option<PathBuf>,
+    /// How many of the lowest finishers fall off the bottom.
+    #[arg(long, default_value_t = 0)]
+    fall_off: usize,
+    /// The most crews allowed; any more fall off the bottom.
+    #[arg(long)]
+    max_crews: Option<usize>,
+    /// Division sizes, head first, adding up to the number of crews
+    /// (default: this year's, with the bottom division resized to fit).
+    #[arg(long, value_delimiter = ',')]
+    divs: Vec<u8>,
+    #[arg(long, value_enum, default_value = "text")]
+    format: Format,
+    #[command(flatten)]
+    source: SourceArgs,
+    /// Where to write the start order (default: standard output).
+    #[arg(short, long)]
+    output: Option<PathBuf>,
+}
+
+fn read_getting_on(path: &Path) -> Result<Vec<String>, Box<dyn Error>> {
+    Ok(std::fs::read_to_string(path)?
+        .lines()
+        .map(str::trim)
+        .filter(|x| !x.is_empty())
+        .map(String::from)
+        .collect())
+}
+
+fn format(
+    order: &StartOrder,
+    format: Format,
+    title: &str,
+    days: u8,
+    name: impl Fn(&str) -> String,
+) -> String {
+    let mut out = String::new();
+
+    match format {
+        Format::Text => {
+            writeln!(out, "{title}").unwrap();
+
+            let mut position = 1;
+
+            for (i, division) in order.divisions().iter().enumerate() {
+                writeln!(out, "division {}", i + 1).unwrap();
+
+                for alias in *division {
+                    writeln!(out, "{position:>4} {alias:<12} {}", name(alias)).unwrap();
+                    position += 1;
+                }
+            }
+
+            if !order.fallen_off.is_empty() {
+                writeln!(out, "fallen off: {}", order.fallen_off.join(", ")).unwrap();
+            }
+        }
+        Format::Csv => {
+            writeln!(out, "position,division,place,alias,name").unwrap();
+
+            let mut position = 1;
+
+            for (i, division) in order.divisions().iter().enumerate() {
+                for (place, alias) in division.iter().enumerate() {
+                    writeln!(
+                        out,
+                        "{position},{},{},{alias},\"{}\"",
+                        i + 1,
+                        place + 1,
+                        name(alias).replace('"', "\"\"")
+                    )
+                    .unwrap();
+                    position += 1;
+                }
+            }
+        }
+        Format::Dat => {
+            let divs: Vec<String> = order
+                .crews_per_division
+                .iter()
+                .map(|x| x.to_string())
+                .collect();
+
+            writeln!(out, "{DAYS}: {days}").unwrap();
+            writeln!(out, "{DIVS}: {}", divs.join(",")).unwrap();
+
+            for alias in &order.crews {
+                writeln!(out, "{alias}").unwrap();
+            }
+        }
+    }
+
+    out
+}
+
+// Chart files are encrypted like the rest of the CD-ROM's files, so that the
+// output can be read back alongside them.
+fn encrypt(text: &str) -> Result<Vec<u8>, std::io::Error> {
+    let mut bytes = vec![];
+    decode::encode(&mut text.as_bytes(), &mut bytes, decode::DEFAULT_KEY)?;
+    Ok(bytes)
+}
+
+pub(crate) fn run(args: &StartOrderArgs) -> Result<(), Box<dyn Error>> {
+    let dataset = args
+        .source
+        .load(&[args.competition], Some(args.year..=args.year))?;
+
+    let getting_on = match &args.getting_on {
+        Some(path) => read_getting_on(path)?,
+        None => vec![],
+    };
+
+    let rules = Rules {
+        fall_off: args.fall_off,
+        max_crews: args.max_crews,
+        crews_per_division: args.divs.clone(),
+    };
+
+    let order = start_order::next_year(
+        &dataset,
+        args.competition,
+        args.year,
+        &args.leaving,
+        &getting_on,
+        &rules,
+    )
+    .ok_or_else(|| format!("No results for the {} in {}", args.competition, args.year))?;
+
+    if order.places() != order.crews.len() {
+        return Err(format!(
+            "--divs has room for {} crews, but the start order has {}",
+            order.places(),
+            order.crews.len()
+        )
+        .into());
+    }
+
+    let days = dataset
+        .chart(args.competition, args.year)
+        .map_or(4, |chart| chart.days);
+
+    let name = |alias: &str| {
+        dataset
+            .crew(args.competition, alias)
+            .map_or_else(|| String::from(alias), |crew| crew.name.clone())
+    };
+
+    let title = format!("{} {}", args.competition, args.year + 1);
+    let text = format(&order, args.format, &title, days, name);
+
+    let bytes = match args.format {
+        Format::Dat => encrypt(&text)?,
+        Format::Text | Format::Csv => text.into_bytes(),
+    };
+
+    match &args.output {
+        Some(output) => std::fs::write(output, bytes)?,
+        None => std::io::stdout().write_all(&bytes)?,
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use the_bumps::decode::Decoder;
+    use the_bumps::start_order::StartOrder;
+    use the_bumps::year::YearChart;
+
+    use super::{encrypt, format, Format};
+
+    #[test]
+    fn dat_reads_back_as_a_chart() {
+        let order = StartOrder {
+            crews: ["a1", "b1", "a2"].map(String::from).to_vec(),
+            crews_per_division: vec![2, 1],
+            fallen_off: vec![],
+        };
+
+        let text = format(&order, Format::Dat, "title", 4, |x: &str| x.to_string());
+        let bytes = encrypt(&text).unwrap();
+        assert_ne!(bytes, text.as_bytes());
+
+        let mut decoder = Decoder::new(bytes.as_slice()).unwrap();
+        let chart = YearChart::from_file(&mut decoder, Path::new("2001.dat")).unwrap();
+
+        assert_eq!(chart.days, 4);
+        assert_eq!(chart.crews_per_division, [2, 1]);
+        assert_eq!(chart.unknown_headers().count(), 0);
+    }
+}
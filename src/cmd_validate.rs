@@ -0,0 +1,293 @@
+// Consistency checks for the CD-ROM data files. Unlike the loaders used by
+// `query` and `db`, this carries on past problems, so that a single run
+// reports everything wrong with a dataset.
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::Display;
+use std::path::Path;
+use std::path::PathBuf;
+
+use clap::Parser;
+
+use the_bumps::colleges::Clubs;
+use the_bumps::crew::CrewRecord;
+use the_bumps::dataset;
+use the_bumps::events::{Events, Outcome};
+use the_bumps::raw;
+use the_bumps::rw2;
+use the_bumps::source::{self, Issue};
+use the_bumps::year::YearChart;
+use the_bumps::BumpsError;
+use the_bumps::Competition;
+
+#[derive(Parser, Debug)]
+pub(crate) struct Validate {
+    #[arg(long)]
+    data_dir: PathBuf,
+    /// Only check these competitions (default: all of them).
+    #[arg(long, value_enum)]
+    competition: Vec<Competition>,
+}
+
+enum Problem {
+    // A file that couldn't be loaded at all.
+    Load(BumpsError),
+    Check {
+        file: PathBuf,
+        crew: Option<String>,
+        message: String,
+    },
+}
+
+impl Display for Problem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Load(err) => write!(f, "{err}"),
+            Self::Check {
+                file,
+                crew,
+                message,
+            } => {
+                write!(f, "{}: ", file.display())?;
+
+                if let Some(crew) = crew {
+                    write!(f, "{crew}: ")?;
+                }
+
+                f.write_str(message)
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct Problems(Vec<Problem>);
+
+impl Problems {
+    fn error(&mut self, err: BumpsError) {
+        self.0.push(Problem::Load(err))
+    }
+
+    fn file(&mut self, file: &Path, message: impl Display) {
+        self.0.push(Problem::Check {
+            file: file.to_path_buf(),
+            crew: None,
+            message: message.to_string(),
+        })
+    }
+
+    fn crew(&mut self, file: &Path, crew: &str, message: impl Display) {
+        self.0.push(Problem::Check {
+            file: file.to_path_buf(),
+            crew: Some(String::from(crew)),
+            message: message.to_string(),
+        })
+    }
+}
+
+fn day_name(day: usize) -> String {
+    if day == 0 {
+        String::from("the start order")
+    } else {
+        format!("day {day}")
+    }
+}
+
+fn check_competition(
+    data_dir: &Path,
+    clubs: &Clubs,
+    competition: Competition,
+    problems: &mut Problems,
+) {
+    let raw_path = dataset::raw_path(data_dir, competition);
+    let rw2_path = dataset::rw2_path(data_dir, competition);
+    let chart_path = |year: u32| dataset::chart_path(data_dir, competition, year);
+
+    let rows = match rw2::Row::from_file(&rw2_path) {
+        Ok(rows) => rows,
+        Err(err) => return problems.error(err),
+    };
+
+    let mut raw = match raw::Raw::load(&raw_path) {
+        Ok(raw) => raw,
+        Err(err) => return problems.error(err),
+    };
+
+    let crews = clubs.crews();
+
+    let min_year = rows.iter().filter_map(|x| x.start_year()).min();
+    let max_year = rows.iter().filter_map(|x| x.end_year()).max();
+
+    let charts: BTreeMap<u32, YearChart> = match (min_year, max_year) {
+        (Some(min_year), Some(max_year)) => (min_year..=max_year)
+            .filter_map(|year| match dataset::load_chart(&chart_path(year)) {
+                Ok(chart) => Some((year, chart)),
+                Err(err) => {
+                    problems.error(err);
+                    None
+                }
+            })
+            .collect(),
+        _ => BTreeMap::new(),
+    };
+
+    let (records, issues) =
+        source::slice_positions(&crews, &rows, &charts, &mut raw, &rw2_path, &raw_path, None);
+
+    for issue in issues {
+        match issue {
+            Issue::Load(err) => problems.error(err),
+            Issue::Mismatch {
+                file,
+                crew: Some(crew),
+                message,
+                ..
+            } => problems.crew(&file, &crew, message),
+            Issue::Mismatch {
+                file,
+                crew: None,
+                message,
+                ..
+            } => problems.file(&file, message),
+        }
+    }
+
+    for (year, chart) in &charts {
+        check_year(
+            &raw_path,
+            &chart_path(*year),
+            *year,
+            chart,
+            &records,
+            problems,
+        );
+    }
+}
+
+fn check_year(
+    raw_path: &Path,
+    chart_path: &Path,
+    year: u32,
+    chart: &YearChart,
+    records: &[CrewRecord],
+    problems: &mut Problems,
+) {
+    for day in 0..=(chart.days as usize) {
+        let mut positions: BTreeMap<u8, Vec<&str>> = BTreeMap::new();
+
+        for record in records {
+            if let Some(position) = record.year(year).and_then(|x| x.get(day)) {
+                if *position != 0 {
+                    positions.entry(*position).or_default().push(&record.alias);
+                }
+            }
+        }
+
+        for (position, crews) in &positions {
+            if crews.len() > 1 {
+                problems.file(
+                    raw_path,
+                    format!(
+                        "{year}, {}: {} are all at position {position}",
+                        day_name(day),
+                        crews.join(", ")
+                    ),
+                );
+            }
+        }
+
+        let last = positions.keys().next_back().copied().unwrap_or(0);
+
+        for position in 1..last {
+            if !positions.contains_key(&position) {
+                problems.file(
+                    raw_path,
+                    format!("{year}, {}: no crew at position {position}", day_name(day)),
+                );
+            }
+        }
+
+        if day == 0 && positions.len() as u32 != chart.crews() {
+            problems.file(
+                chart_path,
+                format!(
+                    "DIVS: has room for {} crews, but {} started",
+                    chart.crews(),
+                    positions.len()
+                ),
+            );
+        }
+    }
+
+    let events = Events::infer(records, year);
+    let events_by_start: HashMap<(u8, u8), Outcome> = events
+        .iter()
+        .map(|event| ((event.day, event.start), event.outcome))
+        .collect();
+
+    for event in events.iter() {
+        let context = format!("{year}, day {}", event.day);
+
+        if event.outcome == Outcome::Irregular {
+            problems.crew(
+                raw_path,
+                event.crew,
+                format!(
+                    "{context}: moved from {} to {}, which no bump explains",
+                    event.start, event.finish
+                ),
+            );
+        } else if event.outcome.is_bump()
+            && events_by_start.get(&(event.day, event.finish)) != Some(&Outcome::Bumped)
+        {
+            problems.crew(
+                raw_path,
+                event.crew,
+                format!(
+                    "{context}: moved from {} to {}, but the crew at {} wasn't bumped",
+                    event.start, event.finish, event.finish
+                ),
+            );
+        }
+    }
+}
+
+pub(crate) fn run(args: &Validate) -> Result<(), Box<dyn Error>> {
+    let mut problems = Problems::default();
+
+    let colleges = dataset::colleges_path(&args.data_dir);
+
+    let clubs = match dataset::load_clubs(&colleges) {
+        Ok(clubs) => Some(clubs),
+        Err(err) => {
+            problems.error(err);
+            None
+        }
+    };
+
+    if let Some(clubs) = &clubs {
+        let competitions = if args.competition.is_empty() {
+            Competition::all().to_vec()
+        } else {
+            args.competition.clone()
+        };
+
+        for competition in competitions {
+            check_competition(&args.data_dir, clubs, competition, &mut problems);
+        }
+    }
+
+    for problem in &problems.0 {
+        println!("{problem}");
+    }
+
+    match problems.0.len() {
+        0 => {
+            println!("no problems found");
+            Ok(())
+        }
+        n => Err(format!("{n} problems found").into()),
+    }
+}
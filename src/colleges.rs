@@ -1,22 +1,29 @@
 use std::io::Read;
+use std::path::Path;
 
-#[derive(Debug)]
+use crate::error::BumpsError;
+
+#[derive(Debug, PartialEq, Clone)]
 pub struct Crew {
     pub name: String,
     pub alias: String,
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Clone)]
 pub struct Club {
     pub name: String,
-    _colour: (u8, u8, u8),
+    pub colour: (u8, u8, u8),
     pub crews: Vec<Crew>,
 }
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq, Clone)]
 pub struct Clubs(Vec<Club>);
 
 impl Clubs {
+    pub fn new(clubs: Vec<Club>) -> Self {
+        Clubs(clubs)
+    }
+
     pub fn clubs(&self) -> &Vec<Club> {
         &self.0
     }
@@ -25,11 +32,12 @@ impl Clubs {
         self.0.iter().flat_map(|club| club.crews.iter()).collect()
     }
 
-    // Loads data from colleges.dat.
-    pub fn from_file(file: &mut dyn Read) -> Result<Self, Box<dyn std::error::Error>> {
+    // Loads data from colleges.dat, where `path` is used for error messages.
+    pub fn from_file(file: &mut dyn Read, path: &Path) -> Result<Self, BumpsError> {
         let mut contents = String::new();
 
-        file.read_to_string(&mut contents)?;
+        file.read_to_string(&mut contents)
+            .map_err(BumpsError::decode(path))?;
 
         // File format:
         //
@@ -44,7 +52,7 @@ impl Clubs {
         let mut current_colour = None;
         let mut current_crews = vec![];
 
-        for line in contents.split('\n') {
+        for (line_number, line) in contents.split('\n').enumerate() {
             let line = line.trim();
 
             if line.starts_with('!') {
@@ -56,7 +64,7 @@ impl Clubs {
                     if let Some(current_colour) = current_colour {
                         clubs.push(Club {
                             name: String::from(current_name),
-                            _colour: current_colour,
+                            colour: current_colour,
                             crews: current_crews,
                         });
                     }
@@ -69,12 +77,23 @@ impl Clubs {
                 let inner = &line[1..(line.len() - 1)];
                 current_name = Some(inner);
             } else if current_colour.is_none() {
+                let syntax_error = |message: String| BumpsError::CollegeSyntax {
+                    path: path.to_path_buf(),
+                    line: line_number + 1,
+                    message,
+                };
+
                 let rgb: Vec<u8> = line
                     .split('\t')
                     .map(|x| x.parse::<u8>())
-                    .collect::<Result<Vec<u8>, _>>()?;
-
-                let (r, g, b) = (rgb[0], rgb[1], rgb[2]);
+                    .collect::<Result<Vec<u8>, _>>()
+                    .map_err(|err| syntax_error(format!("invalid colour {line:?}: {err}")))?;
+
+                let [r, g, b] = rgb[..] else {
+                    return Err(syntax_error(format!(
+                        "expected a red, green and blue value, found {line:?}"
+                    )));
+                };
 
                 current_colour = Some((r, g, b));
             } else if let Some((name, alias)) = line.rsplit_once('\t') {
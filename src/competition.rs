@@ -0,0 +1,116 @@
+use std::fmt::Display;
+
+use clap::{Parser, ValueEnum};
+use diesel::{
+    backend::Backend,
+    deserialize::FromSql,
+    serialize::{Output, ToSql},
+    sql_types::VarChar,
+    sqlite::Sqlite,
+    AsExpression, FromSqlRow,
+};
+
+#[derive(
+    Debug,
+    FromSqlRow,
+    AsExpression,
+    Parser,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Clone,
+    ValueEnum,
+    Copy,
+)]
+#[diesel(sql_type = diesel::sql_types::VarChar)]
+pub enum Competition {
+    Early,
+    MenMays,
+    WomenMays,
+    MenLents,
+    WomenLents,
+}
+
+impl FromSql<VarChar, Sqlite> for Competition {
+    fn from_sql(bytes: <Sqlite as Backend>::RawValue<'_>) -> diesel::deserialize::Result<Self> {
+        Ok(Competition::from_slug(
+            <String as FromSql<VarChar, Sqlite>>::from_sql(bytes)?.as_str(),
+        )?)
+    }
+}
+
+impl ToSql<VarChar, Sqlite> for Competition {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Sqlite>) -> diesel::serialize::Result {
+        let result = <str as ToSql<diesel::sql_types::VarChar, Sqlite>>::to_sql(self.slug(), out)?;
+        Ok(result)
+    }
+}
+
+impl Competition {
+    pub fn all() -> [Self; 5] {
+        [
+            Self::Early,
+            Self::MenLents,
+            Self::MenMays,
+            Self::WomenLents,
+            Self::WomenMays,
+        ]
+    }
+
+    pub fn from_slug(s: &str) -> Result<Self, String> {
+        match s {
+            "early" => Ok(Self::Early),
+            "mmays" => Ok(Self::MenMays),
+            "wmays" => Ok(Self::WomenMays),
+            "mlents" => Ok(Self::MenLents),
+            "wlents" => Ok(Self::WomenLents),
+            name => Err(format!("Invalid competition name {name}")),
+        }
+    }
+
+    pub fn slug(&self) -> &'static str {
+        match self {
+            Self::Early => "early",
+            Self::MenMays => "mmays",
+            Self::WomenMays => "wmays",
+            Self::MenLents => "mlents",
+            Self::WomenLents => "wlents",
+        }
+    }
+
+    pub fn raw_name(&self) -> &'static str {
+        match self {
+            Self::Early => "early",
+            Self::MenMays => "mays",
+            Self::WomenMays => "wmays",
+            Self::MenLents => "lents",
+            Self::WomenLents => "wlents",
+        }
+    }
+
+    pub fn charts_name(&self) -> &'static str {
+        match self {
+            Self::Early => "Early",
+            Self::MenMays => "Mays",
+            Self::WomenMays => "WMays",
+            Self::MenLents => "Lents",
+            Self::WomenLents => "WLents",
+        }
+    }
+}
+
+impl Display for Competition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            Self::Early => "early bumps",
+            Self::MenMays => "men's may bumps",
+            Self::WomenMays => "women's may bumps",
+            Self::MenLents => "men's lent bumps",
+            Self::WomenLents => "women's lent bumps",
+        };
+
+        f.write_str(text)
+    }
+}
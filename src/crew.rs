@@ -15,4 +15,92 @@ impl CrewRecord {
     pub fn year(&self, year: u32) -> Option<&Vec<u8>> {
         self.years.get(&year)
     }
+
+    // How many places the crew moved up on each day of `year`, or `None` if it
+    // didn't race on all `days` days.
+    fn moves(&self, year: u32, days: u8) -> Option<Vec<i32>> {
+        let positions = self.year(year)?;
+
+        if positions.len() != days as usize + 1 || positions.contains(&0) {
+            return None;
+        }
+
+        Some(
+            positions
+                .windows(2)
+                .map(|pair| pair[0] as i32 - pair[1] as i32)
+                .collect(),
+        )
+    }
+
+    /// Whether the crew won blades in `year`, which had `days` days: a bump
+    /// (or overbump) on every day.
+    pub fn blades(&self, year: u32, days: u8) -> bool {
+        self.moves(year, days)
+            .is_some_and(|moves| moves.iter().all(|x| matches!(x, 1 | 3 | 5)))
+    }
+
+    /// Whether the crew got spoons in `year`, which had `days` days: bumped
+    /// down on every day.
+    pub fn spoons(&self, year: u32, days: u8) -> bool {
+        self.moves(year, days)
+            .is_some_and(|moves| moves.iter().all(|x| matches!(x, -1 | -3 | -5)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::CrewRecord;
+
+    fn crew(positions: &[u8]) -> CrewRecord {
+        CrewRecord::new(
+            String::from("King's 3"),
+            String::from("kings3"),
+            BTreeMap::from([(2000, positions.to_vec())]),
+        )
+    }
+
+    #[test]
+    fn bumps_every_day() {
+        let crew = crew(&[10, 9, 8, 7, 6]);
+
+        assert!(crew.blades(2000, 4));
+        assert!(!crew.spoons(2000, 4));
+    }
+
+    #[test]
+    fn blades_with_an_overbump() {
+        assert!(crew(&[10, 9, 6, 5, 4]).blades(2000, 4));
+    }
+
+    #[test]
+    fn row_over_isnt_blades() {
+        let crew = crew(&[10, 9, 9, 8, 7]);
+
+        assert!(!crew.blades(2000, 4));
+        assert!(!crew.spoons(2000, 4));
+    }
+
+    #[test]
+    fn missed_a_day() {
+        assert!(!crew(&[10, 9, 0, 8, 7]).blades(2000, 4));
+        assert!(!crew(&[10, 11, 0, 12, 13]).spoons(2000, 4));
+        // Fewer days than the year had.
+        assert!(!crew(&[10, 9, 8, 7]).blades(2000, 4));
+    }
+
+    #[test]
+    fn spoons_after_being_overbumped() {
+        let crew = crew(&[6, 7, 10, 11, 12]);
+
+        assert!(crew.spoons(2000, 4));
+        assert!(!crew.blades(2000, 4));
+    }
+
+    #[test]
+    fn other_years() {
+        assert!(!crew(&[10, 9, 8, 7, 6]).blades(2001, 4));
+    }
 }
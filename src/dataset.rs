@@ -0,0 +1,250 @@
+// Loads the data files from the bumps CD-ROM. Each crew listed in College.dat
+// has a matching record (in the same order) in every competition's .rw2
+// index, which gives the years it competed and the range of bytes holding its
+// positions in the competition's .raw file. Each year takes up one byte for
+// the start position plus one per day, where the number of days comes from
+// that year's chart file. The reading itself is done by `source::CdRom`, so
+// that a `Dataset` can just as well be built from the database.
+
+use std::collections::{BTreeMap, HashMap};
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+
+use crate::club::ClubYear;
+use crate::colleges::{Club, Clubs};
+use crate::competition::Competition;
+use crate::crew::CrewRecord;
+use crate::decode;
+use crate::division::DivisionTable;
+use crate::error::BumpsError;
+use crate::source::{CdRom, DataSource};
+use crate::year::YearChart;
+
+pub fn colleges_path(data_dir: &Path) -> PathBuf {
+    data_dir.join("Data/College.dat")
+}
+
+pub fn raw_path(data_dir: &Path, competition: Competition) -> PathBuf {
+    data_dir.join(format!("Data/{}.raw", competition.raw_name()))
+}
+
+pub fn rw2_path(data_dir: &Path, competition: Competition) -> PathBuf {
+    data_dir.join(format!("Data/{}.rw2", competition.raw_name()))
+}
+
+pub fn chart_path(data_dir: &Path, competition: Competition, year: u32) -> PathBuf {
+    data_dir.join(format!("Charts/{}/{}.dat", competition.charts_name(), year))
+}
+
+fn open(path: &Path) -> Result<decode::Decoder<std::fs::File>, BumpsError> {
+    let file = std::fs::File::open(path).map_err(BumpsError::io(path))?;
+
+    decode::Decoder::new(file).map_err(BumpsError::decode(path))
+}
+
+pub fn load_clubs(path: &Path) -> Result<Clubs, BumpsError> {
+    Clubs::from_file(&mut open(path)?, path)
+}
+
+pub fn load_chart(path: &Path) -> Result<YearChart, BumpsError> {
+    YearChart::from_file(&mut open(path)?, path)
+}
+
+#[derive(Debug, Clone)]
+struct CompetitionData {
+    charts: BTreeMap<u32, YearChart>,
+    crews: HashMap<String, CrewRecord>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Dataset {
+    clubs: Clubs,
+    competitions: BTreeMap<Competition, CompetitionData>,
+}
+
+impl Dataset {
+    /// Load `competitions` from `data_dir`, keeping only the years in `years`
+    /// (or every year, if it's `None`).
+    pub fn load(
+        data_dir: &Path,
+        competitions: &[Competition],
+        years: Option<RangeInclusive<u32>>,
+    ) -> Result<Self, BumpsError> {
+        Self::from_source(&mut CdRom::new(data_dir), competitions, years)
+    }
+
+    /// Load `competitions` from `source`, keeping only the years in `years`
+    /// (or every year, if it's `None`).
+    pub fn from_source(
+        source: &mut dyn DataSource,
+        competitions: &[Competition],
+        years: Option<RangeInclusive<u32>>,
+    ) -> Result<Self, BumpsError> {
+        let clubs = source.clubs()?;
+
+        let competitions = competitions
+            .iter()
+            .map(|competition| {
+                let charts = source.competition_years(*competition, years.as_ref())?;
+                let crews = source
+                    .positions(*competition, years.as_ref())?
+                    .into_iter()
+                    .map(|crew| (crew.alias.clone(), crew))
+                    .collect();
+
+                Ok((*competition, CompetitionData { charts, crews }))
+            })
+            .collect::<Result<_, BumpsError>>()?;
+
+        Ok(Dataset {
+            clubs,
+            competitions,
+        })
+    }
+
+    /// A copy holding only the years before `year`, e.g. to train a model
+    /// without it seeing the results it's asked to predict.
+    pub fn before(&self, year: u32) -> Dataset {
+        let mut dataset = self.clone();
+
+        for data in dataset.competitions.values_mut() {
+            data.charts.retain(|x, _| *x < year);
+
+            for crew in data.crews.values_mut() {
+                crew.years.retain(|x, _| *x < year);
+            }
+        }
+
+        dataset
+    }
+
+    pub fn clubs(&self) -> &Clubs {
+        &self.clubs
+    }
+
+    pub fn competitions(&self) -> impl Iterator<Item = Competition> + '_ {
+        self.competitions.keys().copied()
+    }
+
+    /// The years with a chart file, in order.
+    pub fn years(&self, competition: Competition) -> impl Iterator<Item = u32> + '_ {
+        self.competitions
+            .get(&competition)
+            .into_iter()
+            .flat_map(|data| data.charts.keys().copied())
+    }
+
+    pub fn chart(&self, competition: Competition, year: u32) -> Option<&YearChart> {
+        self.competitions.get(&competition)?.charts.get(&year)
+    }
+
+    pub fn divisions(&self) -> DivisionTable {
+        let mut divisions = DivisionTable::default();
+
+        for (competition, data) in &self.competitions {
+            for (year, chart) in &data.charts {
+                divisions.insert(*competition, *year, chart.divisions());
+            }
+        }
+
+        divisions
+    }
+
+    pub fn crew(&self, competition: Competition, alias: &str) -> Option<&CrewRecord> {
+        self.competitions.get(&competition)?.crews.get(alias)
+    }
+
+    /// Every crew, in College.dat order.
+    pub fn crews(&self, competition: Competition) -> impl Iterator<Item = &CrewRecord> {
+        self.clubs
+            .crews()
+            .into_iter()
+            .filter_map(move |crew| self.crew(competition, &crew.alias))
+    }
+
+    /// Every crew that competed in `year`.
+    pub fn crews_in(
+        &self,
+        competition: Competition,
+        year: u32,
+    ) -> impl Iterator<Item = &CrewRecord> {
+        self.crews(competition)
+            .filter(move |crew| crew.year(year).is_some())
+    }
+
+    /// The crews that competed in `year`, in start order.
+    pub fn start_order(&self, competition: Competition, year: u32) -> Vec<&CrewRecord> {
+        let mut crews: Vec<(u8, &CrewRecord)> = self
+            .crews_in(competition, year)
+            .filter_map(|crew| Some((*crew.year(year)?.first()?, crew)))
+            .filter(|(start, _)| *start != 0)
+            .collect();
+
+        crews.sort_by_key(|(start, _)| *start);
+        crews.into_iter().map(|(_, crew)| crew).collect()
+    }
+
+    /// The crews belonging to the club called `club`, in College.dat order.
+    pub fn club_crews(&self, competition: Competition, club: &str) -> Vec<&CrewRecord> {
+        self.clubs
+            .clubs()
+            .iter()
+            .filter(|x| x.name == club)
+            .flat_map(|x| x.crews.iter())
+            .filter_map(|crew| self.crew(competition, &crew.alias))
+            .collect()
+    }
+
+    pub fn club_of(&self, alias: &str) -> Option<&Club> {
+        self.clubs
+            .clubs()
+            .iter()
+            .find(|club| club.crews.iter().any(|crew| crew.alias == alias))
+    }
+
+    /// A summary of every year in which the club called `club` entered at
+    /// least one boat.
+    pub fn club_years(&self, competition: Competition, club: &str) -> Vec<ClubYear<'_>> {
+        let crews = self.club_crews(competition, club);
+        let mut previous_boats = None;
+        let mut years = vec![];
+
+        for year in self.years(competition) {
+            let finishes: Vec<(&str, u8, u8)> = crews
+                .iter()
+                .filter_map(|crew| {
+                    let positions = crew.year(year)?;
+                    let start = *positions.iter().find(|x| **x != 0)?;
+                    let finish = *positions.iter().rev().find(|x| **x != 0)?;
+
+                    Some((crew.alias.as_str(), start, finish))
+                })
+                .collect();
+
+            let boats = finishes.len() as u32;
+            let boats_change = previous_boats.map(|previous| boats as i32 - previous as i32);
+            previous_boats = Some(boats);
+
+            let (Some(highest), Some(lowest)) = (
+                finishes.iter().min_by_key(|(_, _, finish)| *finish),
+                finishes.iter().max_by_key(|(_, _, finish)| *finish),
+            ) else {
+                continue;
+            };
+
+            years.push(ClubYear {
+                year,
+                boats,
+                net_places: finishes
+                    .iter()
+                    .map(|(_, start, finish)| *start as i32 - *finish as i32)
+                    .sum(),
+                highest: (highest.0, highest.2),
+                lowest: (lowest.0, lowest.2),
+                boats_change,
+            });
+        }
+
+        years
+    }
+}
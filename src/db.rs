@@ -0,0 +1,328 @@
+// Connections to the SQLite database built by `db from-bumps-cd-rom`. The
+// migrations are embedded in the binary, so a database can be created (or
+// brought up to date) without the diesel CLI.
+//
+// `DbSource` reads crews' results back out, in the same shape as the CD-ROM
+// loader produces them, and implements `DataSource` so that a whole `Dataset`
+// can be built from the database.
+
+use std::collections::BTreeMap;
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+
+use diesel::migration::MigrationVersion;
+use diesel::prelude::*;
+use diesel::SqliteConnection;
+use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
+
+use crate::colleges::{self, Clubs};
+use crate::competition::Competition;
+use crate::crew::CrewRecord;
+use crate::db_entry;
+use crate::error::BumpsError;
+use crate::schema::{clubs, competition_years, crews, divisions, entries};
+use crate::source::DataSource;
+use crate::year::YearChart;
+
+pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations");
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Migrations {
+    // Run any pending migrations, creating the tables if need be.
+    Apply,
+    // Fail if any migrations are pending, rather than changing the schema.
+    Check,
+}
+
+fn error(path: &Path, source: Box<dyn std::error::Error + Send + Sync>) -> BumpsError {
+    BumpsError::Db {
+        path: path.to_path_buf(),
+        source,
+    }
+}
+
+/// Open the database at `path` (creating it if it doesn't exist), with
+/// foreign keys enforced, but without touching the schema.
+pub fn open(path: &Path) -> Result<SqliteConnection, BumpsError> {
+    let url = path.to_string_lossy();
+    let mut conn = SqliteConnection::establish(&url).map_err(BumpsError::db(path))?;
+
+    diesel::sql_query("PRAGMA foreign_keys = ON")
+        .execute(&mut conn)
+        .map_err(BumpsError::db(path))?;
+
+    Ok(conn)
+}
+
+/// Open the database at `path`, which must already exist. SQLite would
+/// otherwise create an empty one, leaving a stray file behind after a typo.
+pub fn open_existing(path: &Path) -> Result<SqliteConnection, BumpsError> {
+    if !path.is_file() {
+        return Err(BumpsError::Io {
+            path: path.to_path_buf(),
+            source: std::io::Error::new(std::io::ErrorKind::NotFound, "no such database"),
+        });
+    }
+
+    open(path)
+}
+
+/// Open the database at `path`, and apply or check its migrations. Only
+/// applying them creates the database if it doesn't exist.
+pub fn connect(path: &Path, migrations: Migrations) -> Result<SqliteConnection, BumpsError> {
+    let mut conn = match migrations {
+        Migrations::Apply => open(path)?,
+        Migrations::Check => open_existing(path)?,
+    };
+
+    match migrations {
+        Migrations::Apply => {
+            conn.run_pending_migrations(MIGRATIONS)
+                .map_err(|err| error(path, err))?;
+        }
+        Migrations::Check => {
+            let pending = conn
+                .has_pending_migration(MIGRATIONS)
+                .map_err(|err| error(path, err))?;
+
+            if pending {
+                return Err(error(
+                    path,
+                    "the schema is out of date; run `db migrate` to update it".into(),
+                ));
+            }
+        }
+    }
+
+    Ok(conn)
+}
+
+/// Run any pending migrations, returning the versions applied.
+pub fn migrate(conn: &mut SqliteConnection, path: &Path) -> Result<Vec<String>, BumpsError> {
+    let applied = conn
+        .run_pending_migrations(MIGRATIONS)
+        .map_err(|err| error(path, err))?;
+
+    Ok(applied.iter().map(|x| x.to_string()).collect())
+}
+
+/// Every embedded migration's version, and whether it has been applied.
+pub fn status(conn: &mut SqliteConnection, path: &Path) -> Result<Vec<(String, bool)>, BumpsError> {
+    let applied: Vec<MigrationVersion> =
+        conn.applied_migrations().map_err(|err| error(path, err))?;
+
+    let migrations =
+        diesel::migration::MigrationSource::<diesel::sqlite::Sqlite>::migrations(&MIGRATIONS)
+            .map_err(|err| error(path, err))?;
+
+    Ok(migrations
+        .iter()
+        .map(|migration| {
+            let version = migration.name().version();
+            (migration.name().to_string(), applied.contains(&version))
+        })
+        .collect())
+}
+
+/// Reads results from the database instead of the CD-ROM files, e.g. to build
+/// a [`Dataset`](crate::Dataset) with `Dataset::from_source`.
+pub struct DbSource {
+    conn: SqliteConnection,
+    path: PathBuf,
+}
+
+// (alias, name, year, day, position) rows, ordered by crew, year and day.
+type Row = (String, String, i32, i32, i32);
+
+fn records(rows: Vec<Row>) -> Vec<CrewRecord> {
+    let mut records: Vec<CrewRecord> = vec![];
+
+    for (alias, name, year, _, position) in rows {
+        let record = match records.last_mut() {
+            Some(record) if record.alias == alias => record,
+            _ => {
+                records.push(CrewRecord::new(name, alias, BTreeMap::new()));
+                records.last_mut().unwrap()
+            }
+        };
+
+        record
+            .years
+            .entry(year as u32)
+            .or_default()
+            .push(position as u8);
+    }
+
+    records
+}
+
+impl DbSource {
+    /// Open the database at `path`, which must already be up to date.
+    pub fn open(path: &Path) -> Result<Self, BumpsError> {
+        Ok(DbSource {
+            conn: connect(path, Migrations::Check)?,
+            path: path.to_path_buf(),
+        })
+    }
+
+    /// Read through `conn`, which is already open and up to date, e.g. to an
+    /// in-memory database. `path` is only used in error messages.
+    pub fn new(conn: SqliteConnection, path: &Path) -> Self {
+        DbSource {
+            conn,
+            path: path.to_path_buf(),
+        }
+    }
+}
+
+// Years as stored in the database, with `None` meaning every year.
+fn year_bounds(years: Option<&RangeInclusive<u32>>) -> (i32, i32) {
+    years.map_or((i32::MIN, i32::MAX), |years| {
+        (
+            *years.start() as i32,
+            (*years.end()).min(i32::MAX as u32) as i32,
+        )
+    })
+}
+
+impl DataSource for DbSource {
+    fn clubs(&mut self) -> Result<Clubs, BumpsError> {
+        let clubs: Vec<db_entry::Club> = clubs::table
+            .select(db_entry::Club::as_select())
+            .order(clubs::id)
+            .load(&mut self.conn)
+            .map_err(BumpsError::db(&self.path))?;
+
+        let crews: Vec<db_entry::Crew> = db_entry::Crew::belonging_to(&clubs)
+            .select(db_entry::Crew::as_select())
+            .order(crews::id)
+            .load(&mut self.conn)
+            .map_err(BumpsError::db(&self.path))?;
+
+        let clubs = crews
+            .grouped_by(&clubs)
+            .into_iter()
+            .zip(clubs)
+            .map(|(crews, club)| colleges::Club {
+                name: club.name,
+                colour: (club.red as u8, club.green as u8, club.blue as u8),
+                crews: crews
+                    .into_iter()
+                    .map(|crew| colleges::Crew {
+                        name: crew.name,
+                        alias: crew.alias,
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        Ok(Clubs::new(clubs))
+    }
+
+    fn competition_years(
+        &mut self,
+        competition: Competition,
+        years: Option<&RangeInclusive<u32>>,
+    ) -> Result<BTreeMap<u32, YearChart>, BumpsError> {
+        let (min, max) = year_bounds(years);
+
+        let competition_years: Vec<db_entry::CompetitionYear> = competition_years::table
+            .filter(competition_years::competition.eq(competition))
+            .filter(competition_years::year.between(min, max))
+            .select(db_entry::CompetitionYear::as_select())
+            .order(competition_years::year)
+            .load(&mut self.conn)
+            .map_err(BumpsError::db(&self.path))?;
+
+        let ids: Vec<i32> = competition_years.iter().map(|x| x.id).collect();
+
+        let divisions: Vec<db_entry::Division> = divisions::table
+            .filter(divisions::competition_year_id.eq_any(&ids))
+            .select(db_entry::Division::as_select())
+            .order((divisions::competition_year_id, divisions::division))
+            .load(&mut self.conn)
+            .map_err(BumpsError::db(&self.path))?;
+
+        Ok(competition_years
+            .into_iter()
+            .map(|competition_year| {
+                let sizes = divisions
+                    .iter()
+                    .filter(|x| x.competition_year_id == competition_year.id)
+                    .map(|x| x.crews as u8)
+                    .collect();
+
+                let chart = YearChart::new(competition_year.days as u8, sizes);
+
+                (competition_year.year as u32, chart)
+            })
+            .collect())
+    }
+
+    fn positions(
+        &mut self,
+        competition: Competition,
+        years: Option<&RangeInclusive<u32>>,
+    ) -> Result<Vec<CrewRecord>, BumpsError> {
+        let (min, max) = year_bounds(years);
+
+        let rows: Vec<Row> = entries::table
+            .inner_join(crews::table)
+            .inner_join(competition_years::table)
+            .filter(competition_years::competition.eq(competition))
+            .filter(competition_years::year.between(min, max))
+            .select((
+                crews::alias,
+                crews::name,
+                competition_years::year,
+                entries::day,
+                entries::position,
+            ))
+            .order((crews::id, competition_years::year, entries::day))
+            .load(&mut self.conn)
+            .map_err(BumpsError::db(&self.path))?;
+
+        Ok(records(rows))
+    }
+
+    // One query for the crew's entries, rather than loading every crew's.
+    fn crew(
+        &mut self,
+        competition: Competition,
+        alias: &str,
+        years: Option<&RangeInclusive<u32>>,
+    ) -> Result<Option<CrewRecord>, BumpsError> {
+        let name: Option<String> = crews::table
+            .filter(crews::alias.eq(alias))
+            .select(crews::name)
+            .first(&mut self.conn)
+            .optional()
+            .map_err(BumpsError::db(&self.path))?;
+
+        let Some(name) = name else {
+            return Ok(None);
+        };
+
+        let (min, max) = year_bounds(years);
+
+        let rows: Vec<(i32, i32, i32)> = entries::table
+            .inner_join(crews::table)
+            .inner_join(competition_years::table)
+            .filter(crews::alias.eq(alias))
+            .filter(competition_years::competition.eq(competition))
+            .filter(competition_years::year.between(min, max))
+            .select((competition_years::year, entries::day, entries::position))
+            .order((competition_years::year, entries::day))
+            .load(&mut self.conn)
+            .map_err(BumpsError::db(&self.path))?;
+
+        let rows = rows
+            .into_iter()
+            .map(|(year, day, position)| (alias.to_string(), name.clone(), year, day, position))
+            .collect();
+
+        Ok(records(rows)
+            .pop()
+            .or_else(|| Some(CrewRecord::new(name, alias.to_string(), BTreeMap::new()))))
+    }
+}
@@ -1,103 +1,86 @@
-use std::fmt::Display;
+use diesel::prelude::*;
 
-use clap::{Parser, ValueEnum};
-use diesel::{
-    backend::Backend,
-    deserialize::FromSql,
-    prelude::*,
-    serialize::{Output, ToSql},
-    sql_types::VarChar,
-    sqlite::Sqlite,
-    AsExpression, FromSqlRow,
-};
+use crate::competition::Competition;
+use crate::schema::{clubs, competition_years, crews, divisions, entries};
 
-#[derive(Debug, FromSqlRow, AsExpression, Parser, PartialEq, Clone, ValueEnum, Copy)]
-#[diesel(sql_type = diesel::sql_types::VarChar)]
-pub(crate) enum Competition {
-    Early,
-    MenMays,
-    WomenMays,
-    MenLents,
-    WomenLents,
+#[derive(Debug, PartialEq, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = clubs)]
+pub struct Club {
+    pub id: i32,
+    pub name: String,
+    pub red: i32,
+    pub green: i32,
+    pub blue: i32,
 }
 
-impl FromSql<VarChar, Sqlite> for Competition {
-    fn from_sql(bytes: <Sqlite as Backend>::RawValue<'_>) -> diesel::deserialize::Result<Self> {
-        Ok(Competition::from_slug(<String as FromSql<VarChar, Sqlite>>::from_sql(bytes)?.as_str())?)
-    }
+#[derive(Debug, Insertable)]
+#[diesel(table_name = clubs)]
+pub struct NewClub<'a> {
+    pub name: &'a str,
+    pub red: i32,
+    pub green: i32,
+    pub blue: i32,
 }
 
-impl ToSql<VarChar, Sqlite> for Competition {
-    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Sqlite>) -> diesel::serialize::Result {
-        let result = <str as ToSql<diesel::sql_types::VarChar, Sqlite>>::to_sql(self.slug(), out)?;
-        Ok(result)
-    }
+#[derive(Debug, PartialEq, Queryable, Selectable, Identifiable, Associations)]
+#[diesel(table_name = crews, belongs_to(Club))]
+pub struct Crew {
+    pub id: i32,
+    pub alias: String,
+    pub name: String,
+    pub club_id: i32,
 }
 
-impl Competition {
-    pub(crate) fn from_slug(s : &str) -> Result<Self, String> {
-        match s {
-            "early" => Ok(Self::Early),
-            "mmays" => Ok(Self::MenMays),
-            "wmays" => Ok(Self::WomenMays),
-            "mlents" => Ok(Self::MenLents),
-            "wlents" => Ok(Self::WomenLents),
-            name => Err(format!("Invalid competition name {name}").into()),
-        }
-    }
-
-    pub(crate) fn slug(&self) -> &'static str {
-        match self {
-            Self::Early => "early",
-            Self::MenMays => "mmays",
-            Self::WomenMays => "wmays",
-            Self::MenLents => "mlents",
-            Self::WomenLents => "wlents",
-        }
-    }
+#[derive(Debug, Insertable)]
+#[diesel(table_name = crews)]
+pub struct NewCrew<'a> {
+    pub alias: &'a str,
+    pub name: &'a str,
+    pub club_id: i32,
+}
 
-    pub(crate) fn raw_name(&self) -> &'static str {
-        match self {
-            Self::Early => "early",
-            Self::MenMays => "mays",
-            Self::WomenMays => "wmays",
-            Self::MenLents => "lents",
-            Self::WomenLents => "wlents",
-        }
-    }
+#[derive(Debug, PartialEq, Queryable, Selectable, Identifiable)]
+#[diesel(table_name = competition_years)]
+pub struct CompetitionYear {
+    pub id: i32,
+    pub competition: Competition,
+    pub year: i32,
+    pub days: i32,
+}
 
-    pub(crate) fn charts_name(&self) -> &'static str {
-        match self {
-            Self::Early => "Early",
-            Self::MenMays => "Mays",
-            Self::WomenMays => "WMays",
-            Self::MenLents => "Lents",
-            Self::WomenLents => "WLents",
-        }
-    }
+#[derive(Debug, Insertable)]
+#[diesel(table_name = competition_years)]
+pub struct NewCompetitionYear {
+    pub competition: Competition,
+    pub year: i32,
+    pub days: i32,
 }
 
-impl Display for Competition {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let text = match self {
-            Self::Early => "early bumps",
-            Self::MenMays => "men's may bumps",
-            Self::WomenMays => "women's may bumps",
-            Self::MenLents => "men's may bumps",
-            Self::WomenLents => "women's may bumps",
-        };
+// One row per division, with 1 the top division.
+#[derive(Debug, PartialEq, Queryable, Selectable, Insertable, Associations)]
+#[diesel(table_name = divisions, belongs_to(CompetitionYear))]
+pub struct Division {
+    pub competition_year_id: i32,
+    pub division: i32,
+    pub crews: i32,
+}
 
-        f.write_str(text)
-    }
+// A crew's position at the start of a year (day 0) or the end of a day.
+#[derive(Debug, PartialEq, Queryable, Selectable, Identifiable, Associations)]
+#[diesel(table_name = entries, belongs_to(Crew), belongs_to(CompetitionYear))]
+pub struct Entry {
+    pub id: i32,
+    pub crew_id: i32,
+    pub competition_year_id: i32,
+    pub day: i32,
+    pub position: i32,
 }
 
 #[derive(Debug, Insertable)]
-#[diesel(table_name = crate::schema::entries)]
-pub(crate) struct NewEntry<'a> {
-    pub year: i32,
+#[diesel(table_name = entries)]
+pub struct NewEntry {
+    pub crew_id: i32,
+    pub competition_year_id: i32,
     pub day: i32,
-    pub club: &'a str,
-    pub crew: &'a str,
-    pub competition: Competition,
     pub position: i32,
 }
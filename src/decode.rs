@@ -1,13 +1,17 @@
 // Algorithm for decoding DAT files included in the bumps installation.
 
-use std::error::Error;
 use std::fs::File;
-use std::io::{BufReader, Read};
+use std::io::{BufReader, Read, Write};
 use std::path::Path;
 
+use crate::error::BumpsError;
+
 pub struct Decoder<T: Read> {
     reader: T,
     key: u8,
+    // Set after a newline, so that the next byte is taken as the line's key,
+    // even if it only arrives in a later call to `read`.
+    expecting_key: bool,
 }
 
 impl<T: Read> Decoder<T> {
@@ -17,15 +21,16 @@ impl<T: Read> Decoder<T> {
         let bytes_read = reader.read(&mut buf)?;
 
         if bytes_read == 0 {
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                "Empty data file.",
-            ));
+            return Err(std::io::Error::other("Empty data file."));
         }
 
         let key = buf[0];
 
-        Ok(Decoder { reader, key })
+        Ok(Decoder {
+            reader,
+            key,
+            expecting_key: false,
+        })
     }
 }
 
@@ -38,55 +43,261 @@ impl<T: Read> Read for Decoder<T> {
     /// `c_i' = (c_i ^ (c_0 + i - 1)) & 0x7f`, where i is the offset from the
     /// start of the line.
     fn read(&mut self, buf: &mut [u8]) -> Result<usize, std::io::Error> {
-        let bytes_read = self.reader.read(buf)?;
+        if buf.is_empty() {
+            return Ok(0);
+        }
 
-        let mut skip = 0usize;
-        let mut skip_next = false;
+        loop {
+            let bytes_read = self.reader.read(buf)?;
 
-        for i in 0..bytes_read {
-            if skip_next {
-                skip_next = false;
-                continue;
+            if bytes_read == 0 {
+                return Ok(0);
             }
 
-            buf[i - skip] = buf[i];
+            let mut written = 0usize;
 
-            if buf[i] == b'\r' {
-                continue;
-            }
-
-            if buf[i] == b'\n' {
-                if (i + 1) < buf.len() {
-                    self.key = buf[i + 1];
+            for i in 0..bytes_read {
+                let c = buf[i];
 
+                if self.expecting_key {
                     // we want to remove the key from the output buffer
-                    skip_next = true;
-                    skip += 1;
+                    self.key = c;
+                    self.expecting_key = false;
+                    continue;
+                }
+
+                buf[written] = c;
+                written += 1;
+
+                if c == b'\n' {
+                    self.expecting_key = true;
+                    continue;
                 }
 
+                if c < 0x1f {
+                    // For whatever reason, they don't encrypt whitespace.
+                    continue;
+                }
+
+                buf[written - 1] = (c ^ self.key) & 0x7f;
+
+                self.key = self.key.wrapping_add(1);
+            }
+
+            // If everything we read was a key, returning 0 would look like the
+            // end of the file, so keep reading.
+            if written > 0 {
+                return Ok(written);
+            }
+        }
+    }
+}
+
+/// The key written at the start of every line by the `encode` tool.
+pub const DEFAULT_KEY: u8 = b'A';
+
+/// Whether `key` can start a line of an encoded file. Control bytes would be
+/// mistaken for line breaks or whitespace by anything that splits the file
+/// into lines before decoding it, so only printable ASCII is allowed.
+pub fn is_valid_key(key: u8) -> bool {
+    key.is_ascii_graphic()
+}
+
+pub struct Encoder<W: Write> {
+    writer: W,
+    line_key: u8,
+    key: u8,
+    at_line_start: bool,
+}
+
+impl<W: Write> Encoder<W> {
+    pub fn new(writer: W, line_key: u8) -> Self {
+        Encoder {
+            writer,
+            line_key,
+            key: line_key,
+            at_line_start: true,
+        }
+    }
+}
+
+impl<W: Write> Write for Encoder<W> {
+    /// The inverse of `Decoder::read`: every line is prefixed with a key,
+    /// and each subsequent character is encrypted with the same xor scheme.
+    ///
+    /// Encrypted characters that would land in the whitespace range have
+    /// their top bit set, so that the decoder doesn't leave them alone. The
+    /// decoder masks the top bit off again, so this is lossless for ASCII.
+    fn write(&mut self, buf: &[u8]) -> Result<usize, std::io::Error> {
+        if let Some(c) = buf.iter().find(|c| !c.is_ascii()) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("Unable to encode non-ASCII byte {c:#04x}."),
+            ));
+        }
+
+        let mut out = Vec::with_capacity(buf.len() + 1);
+
+        for &c in buf {
+            if self.at_line_start {
+                out.push(self.line_key);
+                self.key = self.line_key;
+                self.at_line_start = false;
+            }
+
+            if c == b'\n' {
+                out.push(c);
+                self.at_line_start = true;
                 continue;
             }
 
-            if buf[i] < 0x1f {
-                // For whatever reason, they don't encrypt whitespace.
+            if c < 0x1f {
+                out.push(c);
                 continue;
             }
 
-            buf[i - skip] = (buf[i - skip] ^ self.key) & 0x7f;
+            let mut encrypted = (c ^ self.key) & 0x7f;
+
+            if encrypted < 0x1f {
+                encrypted |= 0x80;
+            }
+
+            out.push(encrypted);
 
             self.key = self.key.wrapping_add(1);
         }
 
-        Ok(bytes_read - skip)
+        self.writer.write_all(&out)?;
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> Result<(), std::io::Error> {
+        self.writer.flush()
     }
 }
 
 /// Decrypt the file at `path`, returning a string, or an error.
-pub fn decode(path: &Path) -> Result<String, Box<dyn Error>> {
-    let mut decoded = BufReader::new(Decoder::new(File::open(path)?)?);
+pub fn decode(path: &Path) -> Result<String, BumpsError> {
+    let file = File::open(path).map_err(BumpsError::io(path))?;
+    let mut decoded = BufReader::new(Decoder::new(file).map_err(BumpsError::decode(path))?);
     let mut buf = String::new();
 
-    decoded.read_to_string(&mut buf)?;
+    decoded
+        .read_to_string(&mut buf)
+        .map_err(BumpsError::decode(path))?;
 
     Ok(buf)
 }
+
+/// Encrypt `input`, writing the result to `output`.
+pub fn encode(input: &mut dyn Read, output: &mut dyn Write, key: u8) -> Result<(), std::io::Error> {
+    let mut encoder = Encoder::new(output, key);
+
+    std::io::copy(input, &mut encoder)?;
+    encoder.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    /// A reader that never returns more than `chunk` bytes from a single call
+    /// to `read`, to exercise the decoder's handling of buffer boundaries.
+    struct Chunked<'a> {
+        data: &'a [u8],
+        chunk: usize,
+    }
+
+    impl Read for Chunked<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> Result<usize, std::io::Error> {
+            let n = self.chunk.min(buf.len()).min(self.data.len());
+            buf[..n].copy_from_slice(&self.data[..n]);
+            self.data = &self.data[n..];
+            Ok(n)
+        }
+    }
+
+    fn encrypt(plain: &[u8], key: u8, chunk: usize) -> Vec<u8> {
+        let mut encoder = Encoder::new(Vec::new(), key);
+
+        for part in plain.chunks(chunk) {
+            encoder.write_all(part).unwrap();
+        }
+
+        encoder.writer
+    }
+
+    fn decrypt(encrypted: &[u8], chunk: usize, buf_size: usize) -> Vec<u8> {
+        let decoder = Decoder::new(Chunked {
+            data: encrypted,
+            chunk,
+        })
+        .unwrap();
+        let mut result = Vec::new();
+
+        BufReader::with_capacity(buf_size, decoder)
+            .read_to_end(&mut result)
+            .unwrap();
+
+        result
+    }
+
+    fn plain_text() -> impl Strategy<Value = Vec<u8>> {
+        let line = "[ -~\t]{0,40}";
+        let ending = prop_oneof![Just("\n"), Just("\r\n")];
+
+        (prop::collection::vec((line, ending), 0..8), "[ -~\t]{0,40}")
+            .prop_map(|(lines, last)| {
+                let mut text = String::new();
+
+                for (line, ending) in lines {
+                    text.push_str(&line);
+                    text.push_str(ending);
+                }
+
+                text.push_str(&last);
+                text.into_bytes()
+            })
+            .prop_filter("empty files can't be decoded", |text| !text.is_empty())
+    }
+
+    #[test]
+    fn decodes_key_split_from_newline() {
+        let encrypted = encrypt(b"DAYS: 4\nDIVS: 17,17\n", b'A', 1);
+
+        assert_eq!(decrypt(&encrypted, 8, 8), b"DAYS: 4\nDIVS: 17,17\n");
+        assert_eq!(decrypt(&encrypted, 1, 1), b"DAYS: 4\nDIVS: 17,17\n");
+    }
+
+    #[test]
+    fn decodes_file_without_trailing_newline() {
+        let encrypted = encrypt(b"1st Trinity\t1trin", b'A', 1);
+
+        assert_eq!(decrypt(&encrypted, 64, 64), b"1st Trinity\t1trin");
+    }
+
+    #[test]
+    fn keys() {
+        assert!(is_valid_key(DEFAULT_KEY));
+        assert!(is_valid_key(b'!') && is_valid_key(b'~'));
+        assert!(!is_valid_key(b'\n') && !is_valid_key(b'\t') && !is_valid_key(b' '));
+        assert!(!is_valid_key(0x7f) && !is_valid_key(0x80));
+    }
+
+    proptest! {
+        #[test]
+        fn round_trip(
+            plain in plain_text(),
+            key in any::<u8>(),
+            write_chunk in 1usize..32,
+            read_chunk in 1usize..32,
+            buf_size in 1usize..32,
+        ) {
+            let encrypted = encrypt(&plain, key, write_chunk);
+
+            prop_assert_eq!(decrypt(&encrypted, read_chunk, buf_size), plain);
+        }
+    }
+}
@@ -0,0 +1,118 @@
+// Each year, the crews in a competition are split into divisions, with sizes
+// given by the DIVS: header of the year's chart file. Positions count from 1
+// at the head of the river, across all divisions.
+//
+// The crew at the head of every division but the first is the sandwich boat:
+// after its own race, it rows again at the bottom of the division above.
+
+use std::collections::HashMap;
+
+use crate::competition::Competition;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Place {
+    pub division: u8, // 1 is the top division
+    pub place: u8,    // 1 is the head of the division
+    pub sandwich: bool,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Divisions(Vec<u8>);
+
+impl Divisions {
+    pub fn new(crews_per_division: Vec<u8>) -> Self {
+        Divisions(crews_per_division)
+    }
+
+    /// Find the division containing `position`, or `None` if it is off the
+    /// bottom of the river.
+    pub fn locate(&self, position: u8) -> Option<Place> {
+        if position == 0 {
+            return None;
+        }
+
+        let mut head = 1u32;
+
+        for (i, size) in self.0.iter().enumerate() {
+            let place = position as u32 - head + 1;
+
+            if place <= *size as u32 {
+                return Some(Place {
+                    division: (i + 1) as u8,
+                    place: place as u8,
+                    sandwich: i > 0 && place == 1,
+                });
+            }
+
+            head += *size as u32;
+        }
+
+        None
+    }
+}
+
+/// Divisions for any number of competitions and years.
+#[derive(Debug, Default)]
+pub struct DivisionTable(HashMap<(Competition, u32), Divisions>);
+
+impl DivisionTable {
+    pub fn insert(&mut self, competition: Competition, year: u32, divisions: Divisions) {
+        self.0.insert((competition, year), divisions);
+    }
+
+    pub fn get(&self, competition: Competition, year: u32) -> Option<&Divisions> {
+        self.0.get(&(competition, year))
+    }
+
+    pub fn locate(&self, competition: Competition, year: u32, position: u8) -> Option<Place> {
+        self.get(competition, year)?.locate(position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{DivisionTable, Divisions, Place};
+    use crate::competition::Competition;
+
+    fn place(division: u8, place: u8, sandwich: bool) -> Option<Place> {
+        Some(Place {
+            division,
+            place,
+            sandwich,
+        })
+    }
+
+    #[test]
+    fn locate() {
+        let divisions = Divisions::new(vec![3, 2, 2]);
+
+        assert_eq!(divisions.locate(1), place(1, 1, false));
+        assert_eq!(divisions.locate(3), place(1, 3, false));
+        assert_eq!(divisions.locate(4), place(2, 1, true));
+        assert_eq!(divisions.locate(5), place(2, 2, false));
+        assert_eq!(divisions.locate(6), place(3, 1, true));
+        assert_eq!(divisions.locate(7), place(3, 2, false));
+    }
+
+    #[test]
+    fn off_the_river() {
+        let divisions = Divisions::new(vec![3, 2]);
+
+        assert_eq!(divisions.locate(0), None);
+        assert_eq!(divisions.locate(6), None);
+        assert_eq!(Divisions::new(vec![]).locate(1), None);
+    }
+
+    #[test]
+    fn table() {
+        let mut table = DivisionTable::default();
+        table.insert(Competition::MenMays, 2000, Divisions::new(vec![2, 2]));
+
+        assert_eq!(
+            table.locate(Competition::MenMays, 2000, 3),
+            place(2, 1, true)
+        );
+        assert_eq!(table.locate(Competition::MenMays, 2001, 3), None);
+        assert_eq!(table.locate(Competition::MenLents, 2000, 3), None);
+    }
+}
@@ -0,0 +1,118 @@
+use std::fmt::Display;
+use std::path::{Path, PathBuf};
+
+/// Everything that can go wrong while loading the bumps data. Every variant
+/// carries the path of the offending file, along with where in that file the
+/// problem is, where that's known.
+#[derive(Debug)]
+pub enum BumpsError {
+    /// Opening or reading a file failed.
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    /// A file couldn't be decrypted, e.g. because it was empty.
+    Decode {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    /// College.dat isn't in the expected format. Lines count from 1.
+    CollegeSyntax {
+        path: PathBuf,
+        line: usize,
+        message: String,
+    },
+    /// A .rw2 index is malformed. Records count from 0.
+    Rw2 {
+        path: PathBuf,
+        record: usize,
+        message: String,
+    },
+    /// A .raw file doesn't contain the bytes `start..end`.
+    RawRange {
+        path: PathBuf,
+        start: u32,
+        end: u32,
+        source: std::io::Error,
+    },
+    /// A chart file header is missing or invalid.
+    ChartHeader {
+        path: PathBuf,
+        key: &'static str,
+        message: String,
+    },
+    Db {
+        path: PathBuf,
+        source: Box<dyn std::error::Error + Send + Sync>,
+    },
+}
+
+impl BumpsError {
+    pub fn path(&self) -> &Path {
+        match self {
+            Self::Io { path, .. }
+            | Self::Decode { path, .. }
+            | Self::CollegeSyntax { path, .. }
+            | Self::Rw2 { path, .. }
+            | Self::RawRange { path, .. }
+            | Self::ChartHeader { path, .. }
+            | Self::Db { path, .. } => path,
+        }
+    }
+
+    pub(crate) fn io(path: &Path) -> impl FnOnce(std::io::Error) -> Self + '_ {
+        move |source| Self::Io {
+            path: path.to_path_buf(),
+            source,
+        }
+    }
+
+    pub(crate) fn decode(path: &Path) -> impl FnOnce(std::io::Error) -> Self + '_ {
+        move |source| Self::Decode {
+            path: path.to_path_buf(),
+            source,
+        }
+    }
+
+    pub fn db<E>(path: &Path) -> impl FnOnce(E) -> Self + '_
+    where
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        move |source| Self::Db {
+            path: path.to_path_buf(),
+            source: Box::new(source),
+        }
+    }
+}
+
+impl Display for BumpsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: ", self.path().display())?;
+
+        match self {
+            Self::Io { source, .. } => write!(f, "{source}"),
+            Self::Decode { source, .. } => write!(f, "unable to decode: {source}"),
+            Self::CollegeSyntax { line, message, .. } => write!(f, "line {line}: {message}"),
+            Self::Rw2 {
+                record, message, ..
+            } => write!(f, "record {record}: {message}"),
+            Self::RawRange {
+                start, end, source, ..
+            } => write!(f, "unable to read bytes {start}..{end}: {source}"),
+            Self::ChartHeader { key, message, .. } => write!(f, "{key} header: {message}"),
+            Self::Db { source, .. } => write!(f, "database error: {source}"),
+        }
+    }
+}
+
+impl std::error::Error for BumpsError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io { source, .. }
+            | Self::Decode { source, .. }
+            | Self::RawRange { source, .. } => Some(source),
+            Self::Db { source, .. } => Some(source.as_ref()),
+            Self::CollegeSyntax { .. } | Self::Rw2 { .. } | Self::ChartHeader { .. } => None,
+        }
+    }
+}
@@ -0,0 +1,292 @@
+// A crew's results for a year are stored as its start position followed by
+// its position at the end of each day. This works out what happened to every
+// crew on every day, and who bumped whom.
+//
+// Crews that bump (or are bumped) stop racing, so on any day a crew moves up
+// one place for a bump, three for an overbump (where the two crews ahead of it
+// bumped out) and five for a double overbump. The crews they catch move down
+// by the same amount.
+
+use std::collections::HashMap;
+use std::fmt::Display;
+
+use crate::crew::CrewRecord;
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Outcome {
+    Bump,
+    Overbump,
+    DoubleOverbump,
+    Bumped,
+    RowOver,
+    // Both the crew ahead and the crew behind bumped out, leaving nobody to
+    // race against.
+    TechnicalRowOver,
+    HeadRowOver,
+    // A move that no legal bump explains, e.g. up two places.
+    Irregular,
+}
+
+impl Outcome {
+    pub fn is_bump(&self) -> bool {
+        matches!(self, Self::Bump | Self::Overbump | Self::DoubleOverbump)
+    }
+}
+
+impl Display for Outcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let text = match self {
+            Self::Bump => "bumped",
+            Self::Overbump => "overbumped",
+            Self::DoubleOverbump => "double overbumped",
+            Self::Bumped => "bumped by",
+            Self::RowOver => "rowed over",
+            Self::TechnicalRowOver => "technical row-over",
+            Self::HeadRowOver => "rowed over at the head",
+            Self::Irregular => "irregular move",
+        };
+
+        f.write_str(text)
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Event<'a> {
+    pub crew: &'a str,
+    pub day: u8, // 1 is the first day
+    pub start: u8,
+    pub finish: u8,
+    pub outcome: Outcome,
+    // For bumps, the crew that was caught. For `Bumped`, the crew that did the
+    // catching.
+    pub opponent: Option<&'a str>,
+}
+
+impl Display for Event<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "day {}: {}", self.day, self.outcome)?;
+
+        match self.opponent {
+            Some(opponent) => write!(f, " {opponent}"),
+            None => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Events<'a>(Vec<Event<'a>>);
+
+impl<'a> Events<'a> {
+    /// Infer every crew's outcome on every day of `year`. Crews that did not
+    /// compete that year are ignored.
+    pub fn infer(crews: impl IntoIterator<Item = &'a CrewRecord>, year: u32) -> Self {
+        let crews: Vec<(&'a str, &'a Vec<u8>)> = crews
+            .into_iter()
+            .filter_map(|crew| Some((crew.alias.as_str(), crew.year(year)?)))
+            .collect();
+
+        let days = crews.iter().map(|(_, p)| p.len()).max().unwrap_or(0);
+
+        let mut events = vec![];
+
+        for day in 1..days {
+            // Who started the day in each position, and how far they moved.
+            let starts: HashMap<u8, (&'a str, u8)> = crews
+                .iter()
+                .filter_map(|(alias, positions)| {
+                    let start = *positions.get(day - 1)?;
+                    let finish = *positions.get(day)?;
+
+                    if start == 0 || finish == 0 {
+                        None
+                    } else {
+                        Some((start, (*alias, finish)))
+                    }
+                })
+                .collect();
+
+            let moved = |position: u8| match starts.get(&position) {
+                Some((_, finish)) => *finish != position,
+                None => true,
+            };
+
+            let mut day_events: Vec<Event<'a>> = starts
+                .iter()
+                .map(|(start, (crew, finish))| {
+                    let (start, finish) = (*start, *finish);
+
+                    let outcome = match start as i32 - finish as i32 {
+                        1 => Outcome::Bump,
+                        3 => Outcome::Overbump,
+                        5 => Outcome::DoubleOverbump,
+                        -1 | -3 | -5 => Outcome::Bumped,
+                        0 if start == 1 => Outcome::HeadRowOver,
+                        0 if moved(start - 1) && moved(start.saturating_add(1)) => {
+                            Outcome::TechnicalRowOver
+                        }
+                        0 => Outcome::RowOver,
+                        _ => Outcome::Irregular,
+                    };
+
+                    let opponent = if outcome.is_bump() || outcome == Outcome::Bumped {
+                        starts.get(&finish).map(|(crew, _)| *crew)
+                    } else {
+                        None
+                    };
+
+                    Event {
+                        crew,
+                        day: day as u8,
+                        start,
+                        finish,
+                        outcome,
+                        opponent,
+                    }
+                })
+                .collect();
+
+            day_events.sort_by_key(|event| event.start);
+            events.append(&mut day_events);
+        }
+
+        Events(events)
+    }
+
+    /// Every event, ordered by day, then by start position.
+    pub fn iter(&self) -> impl Iterator<Item = &Event<'a>> {
+        self.0.iter()
+    }
+
+    pub fn crew<'s>(&'s self, alias: &'s str) -> impl Iterator<Item = &'s Event<'a>> {
+        self.iter().filter(move |event| event.crew == alias)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::{Event, Events, Outcome};
+    use crate::crew::CrewRecord;
+
+    const YEAR: u32 = 2000;
+
+    fn crews(results: &[(&str, &[u8])]) -> Vec<CrewRecord> {
+        results
+            .iter()
+            .map(|(alias, positions)| {
+                CrewRecord::new(
+                    alias.to_string(),
+                    alias.to_string(),
+                    BTreeMap::from([(YEAR, positions.to_vec())]),
+                )
+            })
+            .collect()
+    }
+
+    fn event<'a>(events: &'a Events<'a>, alias: &'a str, day: u8) -> &'a Event<'a> {
+        events.crew(alias).find(|event| event.day == day).unwrap()
+    }
+
+    fn outcome(events: &Events, alias: &str) -> (Outcome, Option<String>) {
+        let event = event(events, alias, 1);
+        (event.outcome, event.opponent.map(String::from))
+    }
+
+    #[test]
+    fn bump() {
+        let crews = crews(&[("a", &[1, 2]), ("b", &[2, 1])]);
+        let events = Events::infer(&crews, YEAR);
+
+        assert_eq!(outcome(&events, "b"), (Outcome::Bump, Some("a".into())));
+        assert_eq!(outcome(&events, "a"), (Outcome::Bumped, Some("b".into())));
+    }
+
+    #[test]
+    fn overbump() {
+        // c bumps b, leaving d to catch a.
+        let crews = crews(&[
+            ("a", &[1, 4]),
+            ("b", &[2, 3]),
+            ("c", &[3, 2]),
+            ("d", &[4, 1]),
+        ]);
+        let events = Events::infer(&crews, YEAR);
+
+        assert_eq!(outcome(&events, "d"), (Outcome::Overbump, Some("a".into())));
+        assert_eq!(outcome(&events, "a"), (Outcome::Bumped, Some("d".into())));
+        assert_eq!(outcome(&events, "c"), (Outcome::Bump, Some("b".into())));
+        assert_eq!(outcome(&events, "b"), (Outcome::Bumped, Some("c".into())));
+    }
+
+    #[test]
+    fn double_overbump() {
+        // c bumps b and e bumps d, leaving f to catch a.
+        let crews = crews(&[
+            ("a", &[1, 6]),
+            ("b", &[2, 3]),
+            ("c", &[3, 2]),
+            ("d", &[4, 5]),
+            ("e", &[5, 4]),
+            ("f", &[6, 1]),
+        ]);
+        let events = Events::infer(&crews, YEAR);
+
+        assert_eq!(
+            outcome(&events, "f"),
+            (Outcome::DoubleOverbump, Some("a".into()))
+        );
+        assert_eq!(outcome(&events, "a"), (Outcome::Bumped, Some("f".into())));
+        assert_eq!(outcome(&events, "e"), (Outcome::Bump, Some("d".into())));
+    }
+
+    #[test]
+    fn row_overs() {
+        let crews = crews(&[("a", &[1, 1]), ("b", &[2, 2]), ("c", &[3, 3])]);
+        let events = Events::infer(&crews, YEAR);
+
+        assert_eq!(outcome(&events, "a"), (Outcome::HeadRowOver, None));
+        assert_eq!(outcome(&events, "b"), (Outcome::RowOver, None));
+        assert_eq!(outcome(&events, "c"), (Outcome::RowOver, None));
+    }
+
+    #[test]
+    fn technical_row_over() {
+        // b bumps a and e bumps d, leaving c with nobody to race.
+        let crews = crews(&[
+            ("a", &[1, 2]),
+            ("b", &[2, 1]),
+            ("c", &[3, 3]),
+            ("d", &[4, 5]),
+            ("e", &[5, 4]),
+        ]);
+        let events = Events::infer(&crews, YEAR);
+
+        assert_eq!(outcome(&events, "c"), (Outcome::TechnicalRowOver, None));
+    }
+
+    #[test]
+    fn irregular() {
+        let crews = crews(&[("a", &[1, 3]), ("b", &[2, 2]), ("c", &[3, 1])]);
+        let events = Events::infer(&crews, YEAR);
+
+        assert_eq!(outcome(&events, "a"), (Outcome::Irregular, None));
+        assert_eq!(outcome(&events, "c"), (Outcome::Irregular, None));
+    }
+
+    #[test]
+    fn several_days() {
+        let crews = crews(&[("a", &[1, 2, 1]), ("b", &[2, 1, 2]), ("c", &[0, 3, 3])]);
+        let events = Events::infer(&crews, YEAR);
+
+        assert_eq!(event(&events, "a", 1).outcome, Outcome::Bumped);
+        assert_eq!(event(&events, "a", 2).outcome, Outcome::Bump);
+        assert_eq!(event(&events, "a", 2).opponent, Some("b"));
+        // c didn't race on day 1, and on day 2 the crew ahead bumped out.
+        assert_eq!(events.crew("c").count(), 1);
+        assert_eq!(event(&events, "c", 2).outcome, Outcome::TechnicalRowOver);
+
+        let order: Vec<(u8, u8)> = events.iter().map(|x| (x.day, x.start)).collect();
+        assert_eq!(order, [(1, 1), (1, 2), (2, 1), (2, 2), (2, 3)]);
+    }
+}
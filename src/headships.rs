@@ -0,0 +1,207 @@
+// Works out who finished each year at the head of the river, and at the head
+// of every other division. A division's head is whichever crew finished in its
+// top position, using that year's DIVS: sizes.
+//
+// A headship is won on the last day a crew moved into the top position by
+// bumping the crew that started the day there, or held all week if the crew
+// started there and never left. A crew can also drop into the top position: the
+// bottom crew of a division that is bumped by the sandwich boat ends up at the
+// head of the division below.
+
+use std::collections::HashMap;
+
+use crate::competition::Competition;
+use crate::dataset::Dataset;
+use crate::events::{Events, Outcome};
+
+// How a crew came to finish the year in the top position.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Gained<'a> {
+    // It started the year there and never left.
+    Held,
+    // It moved up into it on `day`, usually by bumping `from`, the crew that
+    // started the day there.
+    Won { day: u8, from: Option<&'a str> },
+    // It was bumped down into it on `day`, by `by`.
+    Dropped { day: u8, by: Option<&'a str> },
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Headship<'a> {
+    pub year: u32,
+    pub division: u8, // 1 is the head of the river
+    pub crew: &'a str,
+    pub gained: Gained<'a>,
+    // Consecutive years the crew has held this headship, counting this one.
+    pub streak: u32,
+    // How many consecutive years the crew held it for in all.
+    pub reign: u32,
+}
+
+/// Every headship in `competition`, ordered by year and then by division.
+pub fn headships(dataset: &Dataset, competition: Competition) -> Vec<Headship<'_>> {
+    let mut headships = vec![];
+    let mut holders: HashMap<u8, (&str, u32)> = HashMap::new();
+
+    for year in dataset.years(competition) {
+        let Some(chart) = dataset.chart(competition, year) else {
+            continue;
+        };
+
+        let crews: Vec<_> = dataset.crews_in(competition, year).collect();
+        let events = Events::infer(crews.iter().copied(), year);
+
+        let mut head = 1u32;
+        let mut current = HashMap::new();
+
+        for (i, size) in chart.crews_per_division.iter().enumerate() {
+            let division = (i + 1) as u8;
+            let position = head as u8;
+            head += *size as u32;
+
+            let holder = crews.iter().find(|crew| {
+                crew.year(year).and_then(|positions| positions.last()) == Some(&position)
+            });
+
+            let Some(holder) = holder else {
+                continue;
+            };
+
+            // The last time the crew moved into the position, which is either
+            // up, by bumping, or down, by being bumped.
+            let arrival = events
+                .crew(&holder.alias)
+                .filter(|event| event.finish == position && event.start != position)
+                .last();
+
+            let gained = match arrival {
+                None => Gained::Held,
+                Some(event) if event.outcome.is_bump() => Gained::Won {
+                    day: event.day,
+                    from: event.opponent,
+                },
+                Some(event) if event.outcome == Outcome::Bumped => Gained::Dropped {
+                    day: event.day,
+                    by: event.opponent,
+                },
+                Some(event) if event.start > event.finish => Gained::Won {
+                    day: event.day,
+                    from: None,
+                },
+                Some(event) => Gained::Dropped {
+                    day: event.day,
+                    by: None,
+                },
+            };
+
+            let streak = match holders.get(&division) {
+                Some((crew, streak)) if *crew == holder.alias => streak + 1,
+                _ => 1,
+            };
+
+            current.insert(division, (holder.alias.as_str(), streak));
+
+            headships.push(Headship {
+                year,
+                division,
+                crew: &holder.alias,
+                gained,
+                streak,
+                reign: streak,
+            });
+        }
+
+        holders = current;
+    }
+
+    // Walk backwards so that each reign's length reaches its earlier years.
+    let mut reigns: HashMap<(u8, &str), u32> = HashMap::new();
+
+    for headship in headships.iter_mut().rev() {
+        let key = (headship.division, headship.crew);
+
+        headship.reign = match reigns.get(&key) {
+            Some(reign) if headship.streak < *reign => *reign,
+            _ => headship.streak,
+        };
+
+        if headship.streak == 1 {
+            reigns.remove(&key);
+        } else {
+            reigns.insert(key, headship.reign);
+        }
+    }
+
+    headships
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{headships, Gained};
+    use crate::competition::Competition;
+    use crate::source::testing::dataset;
+
+    const COMPETITION: Competition = Competition::MenMays;
+
+    #[test]
+    fn bumped_down_into_the_head_of_a_division() {
+        // Divisions of 2 and 2. b1 starts as the sandwich boat, and bumps a2
+        // (bottom of division 1) on day 1, dropping a2 to the head of
+        // division 2.
+        let dataset = dataset(
+            COMPETITION,
+            &[(2000, 1, &[2, 2])],
+            &[
+                ("A", "a1", 2000, &[1, 1]),
+                ("A", "a2", 2000, &[2, 3]),
+                ("B", "b1", 2000, &[3, 2]),
+                ("B", "b2", 2000, &[4, 4]),
+            ],
+        );
+
+        let headships = headships(&dataset, COMPETITION);
+
+        assert_eq!(headships.len(), 2);
+        assert_eq!(headships[0].crew, "a1");
+        assert_eq!(headships[0].gained, Gained::Held);
+        assert_eq!(headships[1].division, 2);
+        assert_eq!(headships[1].crew, "a2");
+        assert_eq!(
+            headships[1].gained,
+            Gained::Dropped {
+                day: 1,
+                by: Some("b1")
+            }
+        );
+    }
+
+    #[test]
+    fn won_by_bumping() {
+        let dataset = dataset(
+            COMPETITION,
+            &[(2000, 2, &[2, 2]), (2001, 1, &[2, 2])],
+            &[
+                ("A", "a1", 2000, &[1, 2, 2]),
+                ("B", "b1", 2000, &[2, 1, 1]),
+                ("A", "a1", 2001, &[2, 2]),
+                ("B", "b1", 2001, &[1, 1]),
+            ],
+        );
+
+        let headships: Vec<_> = headships(&dataset, COMPETITION)
+            .into_iter()
+            .filter(|x| x.division == 1)
+            .collect();
+
+        assert_eq!(
+            headships[0].gained,
+            Gained::Won {
+                day: 1,
+                from: Some("a1")
+            }
+        );
+        assert_eq!(headships[1].gained, Gained::Held);
+        assert_eq!((headships[1].streak, headships[1].reign), (2, 2));
+        assert_eq!((headships[0].streak, headships[0].reign), (1, 2));
+    }
+}
@@ -0,0 +1,38 @@
+//! Tools for reading the data files from the bumps CD-ROM.
+//!
+//! The data files are encrypted (see [`decode`]). Once decoded, College.dat
+//! lists every club and crew ([`colleges`]), and each competition has a .rw2
+//! index ([`rw2`]) into a .raw file of daily positions ([`raw`]), with a chart
+//! file per year giving the number of days and divisions ([`year`]).
+//! [`Dataset`] loads all of these into a [`CrewRecord`] per crew.
+
+pub mod backtest;
+pub mod club;
+pub mod colleges;
+pub mod competition;
+pub mod crew;
+pub mod dataset;
+pub mod db;
+pub mod db_entry;
+pub mod decode;
+pub mod division;
+pub mod error;
+pub mod events;
+pub mod headships;
+pub mod points;
+pub mod ratings;
+pub mod raw;
+pub mod rw2;
+pub mod schema;
+pub mod simulate;
+pub mod source;
+pub mod start_order;
+pub mod svg;
+pub mod terminal;
+pub mod year;
+
+pub use competition::Competition;
+pub use crew::CrewRecord;
+pub use dataset::Dataset;
+pub use error::BumpsError;
+pub use source::DataSource;
@@ -5,17 +5,23 @@ Rust playground commander.
 use clap::Parser;
 use cmd_query::Query;
 use std::path::PathBuf;
+use the_bumps::decode;
 
+mod cmd_backtest;
+mod cmd_blades;
+mod cmd_chart;
+mod cmd_club;
+mod cmd_cross_check;
 mod cmd_db;
+mod cmd_headships;
+mod cmd_history;
+mod cmd_points;
 mod cmd_query;
-mod colleges;
-mod crew;
-mod db_entry;
-mod decode;
-mod raw;
-mod rw2;
-mod schema;
-mod year;
+mod cmd_ratings;
+mod cmd_simulate;
+mod cmd_start_order;
+mod cmd_validate;
+mod source_args;
 
 #[derive(Parser)]
 #[clap(version = "1.0", author = "Matthew Else <matthewelse1997@gmail.com>")]
@@ -30,25 +36,91 @@ struct Decode {
     file: PathBuf,
 }
 
+#[derive(Parser)]
+struct Encode {
+    #[clap(short, long)]
+    file: PathBuf,
+    #[clap(short, long)]
+    output: Option<PathBuf>,
+    /// The byte to start each line with, e.g. 65 for `A`. Must be printable
+    /// ASCII.
+    #[clap(long, default_value_t = decode::DEFAULT_KEY, value_parser = parse_key)]
+    key: u8,
+}
+
+fn parse_key(value: &str) -> Result<u8, String> {
+    let key = value
+        .parse::<u8>()
+        .map_err(|err| format!("invalid key {value:?}: {err}"))?;
+
+    if !decode::is_valid_key(key) {
+        return Err(format!("key {key} isn't a printable ASCII character"));
+    }
+
+    Ok(key)
+}
+
 #[derive(Parser)]
 enum Subcommand {
     Decode(Decode),
+    Encode(Encode),
     Query(Query),
     #[clap(subcommand)]
     Db(cmd_db::Subcommand),
+    Validate(cmd_validate::Validate),
+    Chart(cmd_chart::Chart),
+    History(cmd_history::History),
+    Headships(cmd_headships::Headships),
+    Blades(cmd_blades::Blades),
+    Club(cmd_club::Club),
+    Points(cmd_points::Points),
+    Ratings(cmd_ratings::Ratings),
+    Simulate(cmd_simulate::Simulate),
+    Backtest(cmd_backtest::Backtest),
+    StartOrder(cmd_start_order::StartOrderArgs),
+    CrossCheck(cmd_cross_check::CrossCheck),
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
-    let opts = Opts::parse();
+fn main() {
+    if let Err(err) = run(Opts::parse()) {
+        eprintln!("error: {err}");
+        std::process::exit(1);
+    }
+}
 
+fn run(opts: Opts) -> Result<(), Box<dyn std::error::Error>> {
     match opts.subcommand {
         Subcommand::Decode(decode) => {
             let data = decode::decode(&decode.file)?;
-            println!("{}", data);
+            print!("{}", data);
+
+            Ok(())
+        }
+        Subcommand::Encode(encode) => {
+            let mut input = std::fs::File::open(&encode.file)?;
+
+            match &encode.output {
+                Some(output) => {
+                    decode::encode(&mut input, &mut std::fs::File::create(output)?, encode.key)?
+                }
+                None => decode::encode(&mut input, &mut std::io::stdout().lock(), encode.key)?,
+            }
 
             Ok(())
         }
         Subcommand::Query(query) => cmd_query::run(&query),
         Subcommand::Db(args) => cmd_db::run(&args),
+        Subcommand::Validate(args) => cmd_validate::run(&args),
+        Subcommand::Chart(args) => cmd_chart::run(&args),
+        Subcommand::History(args) => cmd_history::run(&args),
+        Subcommand::Headships(args) => cmd_headships::run(&args),
+        Subcommand::Blades(args) => cmd_blades::run(&args),
+        Subcommand::Club(args) => cmd_club::run(&args),
+        Subcommand::Points(args) => cmd_points::run(&args),
+        Subcommand::Ratings(args) => cmd_ratings::run(&args),
+        Subcommand::Simulate(args) => cmd_simulate::run(&args),
+        Subcommand::Backtest(args) => cmd_backtest::run(&args),
+        Subcommand::StartOrder(args) => cmd_start_order::run(&args),
+        Subcommand::CrossCheck(args) => cmd_cross_check::run(&args),
     }
 }
@@ -0,0 +1,223 @@
+// Club points tables, in the style of the CUCBC "most successful club"
+// tables: each boat scores points for every day's outcome, and a club's total
+// is divided by the number of boats it entered, so that clubs with many boats
+// don't win just by turning up.
+//
+// The rules have varied over the years, so the points for each outcome are
+// configurable. `Scoring::preset` gives the common variants.
+
+use std::collections::BTreeMap;
+
+use clap::ValueEnum;
+
+use crate::competition::Competition;
+use crate::dataset::Dataset;
+use crate::events::{Event, Events, Outcome};
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Scoring {
+    pub bump: f64,
+    pub overbump: f64,
+    pub double_overbump: f64,
+    // These three are usually negative: the crew caught by a bump, an
+    // overbump and a double overbump.
+    pub bumped: f64,
+    pub overbumped: f64,
+    pub double_overbumped: f64,
+    pub row_over: f64,
+    pub head_row_over: f64,
+    // Divide each club's points by the number of boats it entered.
+    pub normalise: bool,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone, Copy, ValueEnum)]
+pub enum Preset {
+    /// One point per place gained, and one per place lost.
+    Places,
+    /// One point per bump, however many places it gained, and one lost per
+    /// bump conceded.
+    Bumps,
+    /// As `places`, plus half a point for rowing over, and a point for rowing
+    /// over at the head.
+    RowOvers,
+}
+
+impl Scoring {
+    pub fn preset(preset: Preset) -> Self {
+        match preset {
+            Preset::Places => Scoring {
+                bump: 1.0,
+                overbump: 3.0,
+                double_overbump: 5.0,
+                bumped: -1.0,
+                overbumped: -3.0,
+                double_overbumped: -5.0,
+                row_over: 0.0,
+                head_row_over: 0.0,
+                normalise: true,
+            },
+            Preset::Bumps => Scoring {
+                bump: 1.0,
+                overbump: 1.0,
+                double_overbump: 1.0,
+                bumped: -1.0,
+                overbumped: -1.0,
+                double_overbumped: -1.0,
+                row_over: 0.0,
+                head_row_over: 0.0,
+                normalise: true,
+            },
+            Preset::RowOvers => Scoring {
+                bump: 1.0,
+                overbump: 3.0,
+                double_overbump: 5.0,
+                bumped: -1.0,
+                overbumped: -3.0,
+                double_overbumped: -5.0,
+                row_over: 0.5,
+                head_row_over: 1.0,
+                normalise: true,
+            },
+        }
+    }
+
+    pub fn points(&self, event: &Event) -> f64 {
+        match event.outcome {
+            Outcome::Bump => self.bump,
+            Outcome::Overbump => self.overbump,
+            Outcome::DoubleOverbump => self.double_overbump,
+            // Bumped crews drop as far as the crew that caught them rose.
+            Outcome::Bumped => match event.finish - event.start {
+                3 => self.overbumped,
+                5 => self.double_overbumped,
+                _ => self.bumped,
+            },
+            Outcome::RowOver | Outcome::TechnicalRowOver => self.row_over,
+            Outcome::HeadRowOver => self.head_row_over,
+            Outcome::Irregular => 0.0,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct ClubPoints<'a> {
+    pub club: &'a str,
+    pub boats: u32,
+    pub points: f64,
+    // `points`, divided by `boats` if the scoring normalises.
+    pub score: f64,
+}
+
+/// The points table for `year`, adding up every club's boats across
+/// `competitions` (e.g. the men's and women's Mays). Best score first.
+pub fn points_table<'a>(
+    dataset: &'a Dataset,
+    competitions: &[Competition],
+    year: u32,
+    scoring: &Scoring,
+) -> Vec<ClubPoints<'a>> {
+    let mut clubs: BTreeMap<&str, (u32, f64)> = BTreeMap::new();
+
+    for competition in competitions {
+        let crews: Vec<_> = dataset.crews_in(*competition, year).collect();
+
+        for crew in &crews {
+            if let Some(club) = dataset.club_of(&crew.alias) {
+                clubs.entry(&club.name).or_default().0 += 1;
+            }
+        }
+
+        for event in Events::infer(crews.iter().copied(), year).iter() {
+            if let Some(club) = dataset.club_of(event.crew) {
+                clubs.entry(&club.name).or_default().1 += scoring.points(event);
+            }
+        }
+    }
+
+    let mut table: Vec<ClubPoints> = clubs
+        .into_iter()
+        .map(|(club, (boats, points))| ClubPoints {
+            club,
+            boats,
+            points,
+            score: if scoring.normalise {
+                points / boats as f64
+            } else {
+                points
+            },
+        })
+        .collect();
+
+    table.sort_by(|a, b| b.score.total_cmp(&a.score));
+
+    table
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{points_table, Preset, Scoring};
+    use crate::competition::Competition;
+    use crate::source::testing::dataset;
+
+    #[test]
+    fn places_balance() {
+        // a2 bumps a1 and b2 bumps b1, leaving b3 to overbump a3.
+        let competition = Competition::MenMays;
+        let dataset = dataset(
+            competition,
+            &[(2000, 1, &[6])],
+            &[
+                ("A", "a1", 2000, &[1, 2]),
+                ("A", "a2", 2000, &[2, 1]),
+                ("A", "a3", 2000, &[3, 6]),
+                ("B", "b1", 2000, &[4, 5]),
+                ("B", "b2", 2000, &[5, 4]),
+                ("B", "b3", 2000, &[6, 3]),
+            ],
+        );
+
+        let scoring = Scoring {
+            normalise: false,
+            ..Scoring::preset(Preset::Places)
+        };
+
+        let table = points_table(&dataset, &[competition], 2000, &scoring);
+        let total: f64 = table.iter().map(|x| x.points).sum();
+
+        assert_eq!(total, 0.0);
+
+        let points = |club: &str| table.iter().find(|x| x.club == club).unwrap().points;
+
+        // A: +1 -1 -3; B: -1 +1 +3.
+        assert_eq!(points("A"), -3.0);
+        assert_eq!(points("B"), 3.0);
+    }
+
+    #[test]
+    fn bumps_count_overbumps_once() {
+        // a2 bumps a1, leaving o1 to overbump v1.
+        let competition = Competition::MenMays;
+        let dataset = dataset(
+            competition,
+            &[(2000, 1, &[4])],
+            &[
+                ("V", "v1", 2000, &[1, 4]),
+                ("A", "a1", 2000, &[2, 3]),
+                ("A", "a2", 2000, &[3, 2]),
+                ("O", "o1", 2000, &[4, 1]),
+            ],
+        );
+
+        let scoring = Scoring {
+            normalise: false,
+            ..Scoring::preset(Preset::Bumps)
+        };
+
+        let table = points_table(&dataset, &[competition], 2000, &scoring);
+        let points = |club: &str| table.iter().find(|x| x.club == club).unwrap().points;
+
+        assert_eq!(points("O"), 1.0);
+        assert_eq!(points("V"), -1.0);
+        assert_eq!(points("A"), 0.0);
+    }
+}
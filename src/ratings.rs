@@ -0,0 +1,380 @@
+// Elo ratings for crews, fitted on every bump and row-over in the dataset.
+// Elo is an online approximation to a Bradley-Terry model: each encounter
+// moves the winner's rating up and the loser's down, by more when the result
+// was unexpected.
+//
+// The encounters are:
+//
+// - a bump (or overbump), which the bumping crew wins against the crew it
+//   caught, and
+// - a row-over, which the crew rowing over wins against the crew that started
+//   behind it, since that crew failed to catch it.
+//
+// A crew keeps its rating from the Lents to the Mays, and from one year to the
+// next, but the rating decays towards the starting rating in between, since
+// crews change. Men's and women's crews are rated separately, and the Early
+// bumps on their own.
+
+use std::collections::HashMap;
+
+use crate::competition::Competition;
+use crate::dataset::Dataset;
+use crate::events::{Events, Outcome};
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Params {
+    pub initial: f64,
+    // The most a rating can move in one encounter.
+    pub k: f64,
+    // How much of a rating's distance from `initial` survives from the Lents
+    // to the Mays...
+    pub season_decay: f64,
+    // ...and from one year to the next.
+    pub year_decay: f64,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Params {
+            initial: 1500.0,
+            k: 24.0,
+            season_decay: 0.9,
+            year_decay: 0.7,
+        }
+    }
+}
+
+/// One crew's rating over one competition.
+#[derive(Debug, PartialEq, Clone)]
+pub struct CrewRating {
+    pub competition: Competition,
+    pub year: u32,
+    pub alias: String,
+    pub start: u8,
+    pub finish: u8,
+    // The rating going into the competition (after decay), and coming out.
+    pub before: f64,
+    pub after: f64,
+    pub wins: u32,
+    pub losses: u32,
+}
+
+impl CrewRating {
+    pub fn change(&self) -> f64 {
+        self.after - self.before
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, Hash, Clone, Copy)]
+enum Pool {
+    Men,
+    Women,
+    Early,
+}
+
+fn pool(competition: Competition) -> Pool {
+    match competition {
+        Competition::MenLents | Competition::MenMays => Pool::Men,
+        Competition::WomenLents | Competition::WomenMays => Pool::Women,
+        Competition::Early => Pool::Early,
+    }
+}
+
+// The order competitions are raced in within a year.
+const CALENDAR: [Competition; 5] = [
+    Competition::MenLents,
+    Competition::WomenLents,
+    Competition::MenMays,
+    Competition::WomenMays,
+    Competition::Early,
+];
+
+/// The chance that a crew rated `rating` beats one rated `opponent`.
+pub fn expected(rating: f64, opponent: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf((opponent - rating) / 400.0))
+}
+
+// Where `competition` in `year` comes in the order of racing.
+fn calendar_index(competition: Competition, year: u32) -> (u32, usize) {
+    let index = CALENDAR.iter().position(|x| *x == competition).unwrap_or(0);
+
+    (year, index)
+}
+
+fn decay(params: &Params, rating: f64, from: (Competition, u32), to: (Competition, u32)) -> f64 {
+    let decay = if from.1 != to.1 {
+        params.year_decay.powi(to.1.saturating_sub(from.1) as i32)
+    } else if from.0 != to.0 {
+        params.season_decay
+    } else {
+        1.0
+    };
+
+    params.initial + (rating - params.initial) * decay
+}
+
+/// Every crew's rating going into `competition` in `year`, using only the
+/// results raced before it. Crews with no earlier results are missing, and
+/// should be given `params.initial`.
+pub fn strengths(
+    ratings: &[CrewRating],
+    params: &Params,
+    competition: Competition,
+    year: u32,
+) -> HashMap<String, f64> {
+    let before = calendar_index(competition, year);
+    let mut latest: HashMap<&str, &CrewRating> = HashMap::new();
+
+    for rating in ratings {
+        if pool(rating.competition) == pool(competition)
+            && calendar_index(rating.competition, rating.year) < before
+        {
+            latest.insert(&rating.alias, rating);
+        }
+    }
+
+    latest
+        .into_iter()
+        .map(|(alias, rating)| {
+            let strength = decay(
+                params,
+                rating.after,
+                (rating.competition, rating.year),
+                (competition, year),
+            );
+
+            (String::from(alias), strength)
+        })
+        .collect()
+}
+
+struct State {
+    rating: f64,
+    year: u32,
+    competition: Competition,
+}
+
+/// Fit ratings on every competition and year in `dataset`, in the order they
+/// were raced. Returns one entry per crew per competition-year, in that order.
+pub fn fit(dataset: &Dataset, params: &Params) -> Vec<CrewRating> {
+    let mut states: HashMap<(Pool, &str), State> = HashMap::new();
+    let mut ratings = vec![];
+
+    let mut years: Vec<u32> = dataset
+        .competitions()
+        .flat_map(|competition| dataset.years(competition))
+        .collect();
+    years.sort_unstable();
+    years.dedup();
+
+    for year in years {
+        for competition in CALENDAR {
+            let pool = pool(competition);
+            let crews: Vec<_> = dataset.crews_in(competition, year).collect();
+
+            if crews.is_empty() {
+                continue;
+            }
+
+            let mut entries: HashMap<&str, CrewRating> = HashMap::new();
+
+            for crew in &crews {
+                let positions = crew.year(year).into_iter().flatten();
+                let raced: Vec<u8> = positions.copied().filter(|x| *x != 0).collect();
+
+                let (Some(start), Some(finish)) = (raced.first(), raced.last()) else {
+                    continue;
+                };
+
+                let rating = match states.get(&(pool, crew.alias.as_str())) {
+                    None => params.initial,
+                    Some(state) => decay(
+                        params,
+                        state.rating,
+                        (state.competition, state.year),
+                        (competition, year),
+                    ),
+                };
+
+                entries.insert(
+                    &crew.alias,
+                    CrewRating {
+                        competition,
+                        year,
+                        alias: crew.alias.clone(),
+                        start: *start,
+                        finish: *finish,
+                        before: rating,
+                        after: rating,
+                        wins: 0,
+                        losses: 0,
+                    },
+                );
+            }
+
+            let events = Events::infer(crews.iter().copied(), year);
+            let mut starts: HashMap<(u8, u8), &str> = HashMap::new();
+
+            for event in events.iter() {
+                starts.insert((event.day, event.start), event.crew);
+            }
+
+            for event in events.iter() {
+                let loser = match event.outcome {
+                    Outcome::Bump | Outcome::Overbump | Outcome::DoubleOverbump => event.opponent,
+                    Outcome::RowOver => starts.get(&(event.day, event.start + 1)).copied(),
+                    _ => None,
+                };
+
+                let Some(loser) = loser else {
+                    continue;
+                };
+
+                let (Some(a), Some(b)) = (
+                    entries.get(event.crew).map(|x| x.after),
+                    entries.get(loser).map(|x| x.after),
+                ) else {
+                    continue;
+                };
+
+                let delta = params.k * (1.0 - expected(a, b));
+
+                if let Some(winner) = entries.get_mut(event.crew) {
+                    winner.after += delta;
+                    winner.wins += 1;
+                }
+
+                if let Some(loser) = entries.get_mut(loser) {
+                    loser.after -= delta;
+                    loser.losses += 1;
+                }
+            }
+
+            // Keep College.dat order, which is stable between runs.
+            for crew in &crews {
+                if let Some(entry) = entries.remove(crew.alias.as_str()) {
+                    states.insert(
+                        (pool, &crew.alias),
+                        State {
+                            rating: entry.after,
+                            year,
+                            competition,
+                        },
+                    );
+
+                    ratings.push(entry);
+                }
+            }
+        }
+    }
+
+    ratings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{expected, fit, strengths, CrewRating, Params};
+    use crate::competition::Competition;
+    use crate::source::testing::dataset;
+
+    const MAYS: Competition = Competition::MenMays;
+
+    fn after<'a>(ratings: &'a [CrewRating], alias: &str, year: u32) -> &'a CrewRating {
+        ratings
+            .iter()
+            .find(|x| x.alias == alias && x.year == year)
+            .unwrap()
+    }
+
+    #[test]
+    fn evenly_matched() {
+        assert_eq!(expected(1500.0, 1500.0), 0.5);
+        assert!((expected(1900.0, 1500.0) - 10.0 / 11.0).abs() < 1e-12);
+        assert!((expected(1500.0, 1700.0) + expected(1700.0, 1500.0) - 1.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn bump() {
+        let dataset = dataset(
+            MAYS,
+            &[(2000, 1, &[2])],
+            &[("A", "a1", 2000, &[1, 2]), ("B", "b1", 2000, &[2, 1])],
+        );
+        let params = Params::default();
+        let ratings = fit(&dataset, &params);
+
+        let (a1, b1) = (after(&ratings, "a1", 2000), after(&ratings, "b1", 2000));
+
+        // Evenly matched, so the bump is worth half of k.
+        assert_eq!(b1.change(), params.k / 2.0);
+        assert_eq!(a1.change(), -params.k / 2.0);
+        assert_eq!((b1.wins, b1.losses, a1.wins, a1.losses), (1, 0, 0, 1));
+    }
+
+    #[test]
+    fn row_over() {
+        let dataset = dataset(
+            MAYS,
+            &[(2000, 1, &[3])],
+            &[
+                ("A", "a1", 2000, &[1, 1]),
+                ("B", "b1", 2000, &[2, 2]),
+                ("C", "c1", 2000, &[3, 3]),
+            ],
+        );
+        let ratings = fit(&dataset, &Params::default());
+
+        // b1 held off c1; a1, at the head, had nobody to race.
+        assert_eq!(after(&ratings, "a1", 2000).change(), 0.0);
+        assert!(after(&ratings, "b1", 2000).change() > 0.0);
+        assert!(after(&ratings, "c1", 2000).change() < 0.0);
+    }
+
+    #[test]
+    fn year_decay() {
+        let dataset = dataset(
+            MAYS,
+            &[(2000, 1, &[2]), (2002, 1, &[2])],
+            &[
+                ("A", "a1", 2000, &[1, 2]),
+                ("B", "b1", 2000, &[2, 1]),
+                ("A", "a1", 2002, &[2, 2]),
+                ("B", "b1", 2002, &[1, 1]),
+            ],
+        );
+        let params = Params::default();
+        let ratings = fit(&dataset, &params);
+
+        let won = after(&ratings, "b1", 2000).after - params.initial;
+        let decayed = params.initial + won * params.year_decay.powi(2);
+
+        assert!((after(&ratings, "b1", 2002).before - decayed).abs() < 1e-9);
+
+        let strength = strengths(&ratings, &params, MAYS, 2001)["b1"];
+        assert!((strength - (params.initial + won * params.year_decay)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn season_decay() {
+        let params = Params::default();
+        let lents = CrewRating {
+            competition: Competition::MenLents,
+            year: 2000,
+            alias: String::from("a1"),
+            start: 1,
+            finish: 1,
+            before: params.initial,
+            after: params.initial + 100.0,
+            wins: 0,
+            losses: 0,
+        };
+        let ratings = [lents];
+
+        let mays = strengths(&ratings, &params, MAYS, 2000)["a1"];
+        assert!((mays - (params.initial + 100.0 * params.season_decay)).abs() < 1e-9);
+
+        // Nothing carries over to the women's races, or back in time.
+        assert!(strengths(&ratings, &params, Competition::WomenMays, 2000).is_empty());
+        assert!(strengths(&ratings, &params, Competition::MenLents, 2000).is_empty());
+    }
+}
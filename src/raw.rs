@@ -1,24 +1,45 @@
 use std::fs::File;
-use std::io::{Error, Read, Seek, SeekFrom};
-use std::path::Path;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+use crate::error::BumpsError;
 
 pub struct Raw {
+    path: PathBuf,
     read: File,
 }
 
 impl Raw {
-    pub fn load(raw: &Path) -> Result<Self, Error> {
-        let reader = File::open(raw)?;
+    pub fn load(raw: &Path) -> Result<Self, BumpsError> {
+        let reader = File::open(raw).map_err(BumpsError::io(raw))?;
 
-        Ok(Raw { read: reader })
+        Ok(Raw {
+            path: raw.to_path_buf(),
+            read: reader,
+        })
     }
 
-    pub fn range(&mut self, start_incl: u32, end_excl: u32) -> Result<Vec<u8>, Error> {
-        let mut result = vec![0; (end_excl - start_incl) as usize];
-        self.read.seek(SeekFrom::Start(start_incl as u64))?;
+    pub fn range(&mut self, start_incl: u32, end_excl: u32) -> Result<Vec<u8>, BumpsError> {
+        let mut result = vec![0; end_excl.saturating_sub(start_incl) as usize];
 
-        self.read.read_exact(&mut result)?;
+        self.read
+            .seek(SeekFrom::Start(start_incl as u64))
+            .and_then(|_| self.read.read_exact(&mut result))
+            .map_err(|source| BumpsError::RawRange {
+                path: self.path.clone(),
+                start: start_incl,
+                end: end_excl,
+                source,
+            })?;
 
         Ok(result)
     }
+
+    pub fn size(&self) -> Result<u64, BumpsError> {
+        Ok(self
+            .read
+            .metadata()
+            .map_err(BumpsError::io(&self.path))?
+            .len())
+    }
 }
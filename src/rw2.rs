@@ -1,9 +1,14 @@
 use nom::IResult;
 use nom::{multi::many0, number::complete::le_u32};
 use std::fs::File;
-use std::io::{Error, ErrorKind, Read};
+use std::io::Read;
 use std::path::Path;
 
+use crate::error::BumpsError;
+
+// Each record is six little-endian u32s.
+const RECORD_SIZE: usize = 24;
+
 #[derive(Debug, PartialEq, Clone)]
 pub struct Details {
     years_active: (u32, u32),
@@ -42,15 +47,47 @@ fn row(input: &[u8]) -> IResult<&[u8], Vec<Option<Row>>> {
     many0(entry)(input)
 }
 impl Row {
-    pub fn from_file(rw2: &Path) -> Result<Vec<Self>, Error> {
+    pub fn from_file(path: &Path) -> Result<Vec<Self>, BumpsError> {
         let mut s = Vec::new();
-        let mut rw2 = File::open(rw2)?;
-        let _bytes_read = rw2.read_to_end(&mut s)?;
+        let mut rw2 = File::open(path).map_err(BumpsError::io(path))?;
+        let _bytes_read = rw2.read_to_end(&mut s).map_err(BumpsError::io(path))?;
+
+        let error = |record: usize, message: String| BumpsError::Rw2 {
+            path: path.to_path_buf(),
+            record,
+            message,
+        };
 
-        match row(&s) {
-            Ok((_, rows)) => Ok(rows.into_iter().flatten().collect()),
-            Err(_) => Err(Error::new(ErrorKind::Other, "Unable to parse rw2 file.")),
+        let rows = match row(&s) {
+            Ok(([], rows)) => rows,
+            Ok((rest, _)) => {
+                return Err(error(
+                    s.len() / RECORD_SIZE,
+                    format!("{} trailing bytes after the last record", rest.len()),
+                ))
+            }
+            Err(err) => return Err(error(0, format!("unable to parse rw2 file: {err}"))),
+        };
+
+        for (record, row) in rows.iter().enumerate() {
+            if let Some(Row::Competed(details)) = row {
+                if details.years_active.0 > details.years_active.1 {
+                    return Err(error(
+                        record,
+                        format!("years {:?} are out of order", details.years_active),
+                    ));
+                }
+
+                if details.indices.0 > details.indices.1 {
+                    return Err(error(
+                        record,
+                        format!("indices {:?} are out of order", details.indices),
+                    ));
+                }
+            }
         }
+
+        Ok(rows.into_iter().flatten().collect())
     }
 
     pub fn start_year(&self) -> Option<u32> {
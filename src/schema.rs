@@ -1,13 +1,54 @@
 // @generated automatically by Diesel CLI.
 
 diesel::table! {
-    entries (id) {
+    clubs (id) {
         id -> Integer,
-        club -> Text,
-        crew -> Text,
+        name -> Text,
+        red -> Integer,
+        green -> Integer,
+        blue -> Integer,
+    }
+}
+
+diesel::table! {
+    competition_years (id) {
+        id -> Integer,
+        competition -> Text,
         year -> Integer,
+        days -> Integer,
+    }
+}
+
+diesel::table! {
+    crews (id) {
+        id -> Integer,
+        alias -> Text,
+        name -> Text,
+        club_id -> Integer,
+    }
+}
+
+diesel::table! {
+    divisions (competition_year_id, division) {
+        competition_year_id -> Integer,
+        division -> Integer,
+        crews -> Integer,
+    }
+}
+
+diesel::table! {
+    entries (id) {
+        id -> Integer,
+        crew_id -> Integer,
+        competition_year_id -> Integer,
         day -> Integer,
         position -> Integer,
-        competition -> Text,
     }
 }
+
+diesel::joinable!(crews -> clubs (club_id));
+diesel::joinable!(divisions -> competition_years (competition_year_id));
+diesel::joinable!(entries -> competition_years (competition_year_id));
+diesel::joinable!(entries -> crews (crew_id));
+
+diesel::allow_tables_to_appear_in_same_query!(clubs, competition_years, crews, divisions, entries,);
@@ -0,0 +1,309 @@
+// Monte Carlo simulation of a whole bumps competition from a start order.
+//
+// Each day, the divisions race from the bottom up. The crew at the head of
+// every division but the first is the sandwich boat: it races at the head of
+// its own division, and then again at the bottom of the division above.
+//
+// Within a race, every crew chases the nearest crew ahead of it that is still
+// racing, and catches it at a random time, sooner the stronger it is relative
+// to its target. Catches are played out in order: both crews stop racing once
+// one catches the other, so a crew whose target is caught is left chasing the
+// next crew ahead, three places up (an overbump), and so on. Crews that are
+// still racing at the end row over.
+
+use std::cmp::Ordering;
+
+use rand::Rng;
+
+use crate::ratings;
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Race {
+    // Crew aliases, head of the river first, with their strengths as ratings
+    // (see `ratings`).
+    pub crews: Vec<(String, f64)>,
+    pub crews_per_division: Vec<u8>,
+    pub days: u8,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Params {
+    // How often evenly matched crews catch one another: a crew chasing an
+    // equal crew, with nobody else racing, catches it with probability
+    // 1 - e^-bump_rate.
+    pub bump_rate: f64,
+}
+
+impl Default for Params {
+    fn default() -> Self {
+        Params { bump_rate: 0.5 }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Outcomes {
+    pub crews: Vec<String>,
+    pub runs: u32,
+    // For each crew, how many runs it finished in each position (from 1).
+    pub finishes: Vec<Vec<u32>>,
+    pub blades: Vec<u32>,
+    pub spoons: Vec<u32>,
+}
+
+impl Outcomes {
+    /// The chance that crew `crew` (indexed as in the start order) finishes at
+    /// `position`.
+    pub fn finish_probability(&self, crew: usize, position: u8) -> f64 {
+        let count = self.finishes[crew]
+            .get(position as usize - 1)
+            .copied()
+            .unwrap_or(0);
+
+        count as f64 / self.runs as f64
+    }
+
+    pub fn expected_finish(&self, crew: usize) -> f64 {
+        self.finishes[crew]
+            .iter()
+            .enumerate()
+            .map(|(i, count)| (i + 1) as f64 * *count as f64)
+            .sum::<f64>()
+            / self.runs as f64
+    }
+
+    pub fn blades_probability(&self, crew: usize) -> f64 {
+        self.blades[crew] as f64 / self.runs as f64
+    }
+
+    pub fn spoons_probability(&self, crew: usize) -> f64 {
+        self.spoons[crew] as f64 / self.runs as f64
+    }
+}
+
+// The positions (from 0) racing in each division's race, bottom division
+// first. Each race includes the sandwich boat's position, if there is one.
+fn races(race: &Race) -> Vec<Vec<usize>> {
+    let crews = race.crews.len();
+    let mut races: Vec<Vec<usize>> = vec![];
+    let mut head = 0;
+
+    for size in &race.crews_per_division {
+        let end = (head + *size as usize).min(crews);
+
+        if head < end {
+            races.push((head..(end + 1).min(crews)).collect());
+        }
+
+        head = end;
+    }
+
+    // Crews past the end of DIVS: race with the last division.
+    if let Some(last) = races.last_mut() {
+        last.extend((last.last().copied().unwrap_or(0) + 1)..crews);
+    }
+
+    races.reverse();
+    races
+}
+
+// Race the crews at `positions` (head first), updating `order`, which maps
+// positions to crews.
+fn race_division(
+    order: &mut [usize],
+    positions: &[usize],
+    strengths: &[f64],
+    params: &Params,
+    rng: &mut impl Rng,
+) {
+    let mut racing = vec![true; positions.len()];
+    let mut time = 0.0;
+
+    loop {
+        // The next catch: (time, chaser, target), as indices into `positions`.
+        let mut next: Option<(f64, usize, usize)> = None;
+
+        for chaser in 1..positions.len() {
+            if !racing[chaser] {
+                continue;
+            }
+
+            let Some(target) = (0..chaser).rev().find(|x| racing[*x]) else {
+                continue;
+            };
+
+            let gap = (chaser - target) as f64;
+            let p = ratings::expected(
+                strengths[order[positions[chaser]]],
+                strengths[order[positions[target]]],
+            );
+            let rate = 2.0 * params.bump_rate * p / gap;
+            let at = time - (1.0 - rng.gen::<f64>()).ln() / rate;
+
+            if next.is_none_or(|(earliest, _, _)| at < earliest) {
+                next = Some((at, chaser, target));
+            }
+        }
+
+        match next {
+            Some((at, chaser, target)) if at < 1.0 => {
+                order.swap(positions[chaser], positions[target]);
+                racing[chaser] = false;
+                racing[target] = false;
+                time = at;
+            }
+            _ => return,
+        }
+    }
+}
+
+/// Simulate `race` `runs` times.
+pub fn simulate(race: &Race, params: &Params, runs: u32, rng: &mut impl Rng) -> Outcomes {
+    let crews = race.crews.len();
+    let strengths: Vec<f64> = race.crews.iter().map(|(_, x)| *x).collect();
+    let races = races(race);
+
+    let mut outcomes = Outcomes {
+        crews: race.crews.iter().map(|(x, _)| x.clone()).collect(),
+        runs,
+        finishes: vec![vec![0; crews]; crews],
+        blades: vec![0; crews],
+        spoons: vec![0; crews],
+    };
+
+    for _ in 0..runs {
+        // order[position] is the crew at that position.
+        let mut order: Vec<usize> = (0..crews).collect();
+        let mut ups = vec![0u8; crews];
+        let mut downs = vec![0u8; crews];
+
+        for _ in 0..race.days {
+            let mut before = vec![0; crews];
+
+            for (position, crew) in order.iter().enumerate() {
+                before[*crew] = position;
+            }
+
+            for positions in &races {
+                race_division(&mut order, positions, &strengths, params, rng);
+            }
+
+            for (position, crew) in order.iter().enumerate() {
+                match position.cmp(&before[*crew]) {
+                    Ordering::Less => ups[*crew] += 1,
+                    Ordering::Greater => downs[*crew] += 1,
+                    Ordering::Equal => {}
+                }
+            }
+        }
+
+        for (position, crew) in order.iter().enumerate() {
+            outcomes.finishes[*crew][position] += 1;
+
+            if race.days > 0 && ups[*crew] == race.days {
+                outcomes.blades[*crew] += 1;
+            }
+
+            if race.days > 0 && downs[*crew] == race.days {
+                outcomes.spoons[*crew] += 1;
+            }
+        }
+    }
+
+    outcomes
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+
+    use super::{race_division, races, simulate, Params, Race};
+
+    // Gaps of 4000 rating points make catches all but certain one way round,
+    // and all but impossible the other, given a high enough bump rate.
+    const FAST: Params = Params { bump_rate: 1e6 };
+
+    fn race(strengths: &[f64], crews_per_division: Vec<u8>, days: u8) -> Race {
+        Race {
+            crews: strengths
+                .iter()
+                .enumerate()
+                .map(|(i, strength)| (format!("c{i}"), *strength))
+                .collect(),
+            crews_per_division,
+            days,
+        }
+    }
+
+    #[test]
+    fn races_include_the_sandwich_boat() {
+        let race = race(&[0.0; 5], vec![3, 2], 4);
+
+        assert_eq!(races(&race), [vec![3, 4], vec![0, 1, 2, 3]]);
+    }
+
+    #[test]
+    fn races_past_the_end_of_divs() {
+        let race = race(&[0.0; 6], vec![2, 2], 4);
+
+        assert_eq!(races(&race), [vec![2, 3, 4, 5], vec![0, 1, 2]]);
+    }
+
+    #[test]
+    fn no_catches_without_a_bump_rate() {
+        let mut order = vec![0, 1, 2, 3];
+        let mut rng = StdRng::seed_from_u64(0);
+        let params = Params { bump_rate: 0.0 };
+
+        race_division(&mut order, &[0, 1, 2, 3], &[0.0; 4], &params, &mut rng);
+
+        assert_eq!(order, [0, 1, 2, 3]);
+    }
+
+    #[test]
+    fn bump() {
+        let mut order = vec![0, 1];
+        let mut rng = StdRng::seed_from_u64(0);
+
+        race_division(&mut order, &[0, 1], &[0.0, 4000.0], &FAST, &mut rng);
+
+        assert_eq!(order, [1, 0]);
+    }
+
+    #[test]
+    fn overbump() {
+        // 2 catches 1 straight away, leaving 3, which can't catch 2, to chase
+        // and catch 0.
+        let mut order = vec![0, 1, 2, 3];
+        let mut rng = StdRng::seed_from_u64(0);
+        let strengths = [4000.0, 0.0, 12000.0, 8000.0];
+
+        race_division(&mut order, &[0, 1, 2, 3], &strengths, &FAST, &mut rng);
+
+        assert_eq!(order, [3, 2, 1, 0]);
+    }
+
+    #[test]
+    fn crews_stop_once_they_have_bumped() {
+        // 1 catches 0 almost at once. 2 could easily catch 0, but both crews
+        // ahead of it have stopped, so it rows over.
+        let mut order = vec![0, 1, 2];
+        let mut rng = StdRng::seed_from_u64(0);
+        let strengths = [0.0, 4000.0, 2000.0];
+
+        race_division(&mut order, &[0, 1, 2], &strengths, &FAST, &mut rng);
+
+        assert_eq!(order, [1, 0, 2]);
+    }
+
+    #[test]
+    fn blades_and_spoons() {
+        let race = race(&[0.0, 4000.0], vec![2], 1);
+        let outcomes = simulate(&race, &FAST, 10, &mut StdRng::seed_from_u64(0));
+
+        assert_eq!(outcomes.blades, [0, 10]);
+        assert_eq!(outcomes.spoons, [10, 0]);
+        assert_eq!(outcomes.finish_probability(1, 1), 1.0);
+        assert_eq!(outcomes.expected_finish(0), 2.0);
+    }
+}
@@ -0,0 +1,735 @@
+// Where results come from. The CD-ROM files (`CdRom`) and the SQLite database
+// (`db::DbSource`) both implement `DataSource`, and `Dataset` can be built
+// from either, so everything built on a `Dataset` works with both.
+
+use std::collections::{BTreeMap, HashMap};
+use std::ops::RangeInclusive;
+use std::path::{Path, PathBuf};
+
+use crate::colleges::{Clubs, Crew};
+use crate::competition::Competition;
+use crate::crew::CrewRecord;
+use crate::dataset::{self, Dataset};
+use crate::error::BumpsError;
+use crate::raw;
+use crate::rw2;
+use crate::year::YearChart;
+
+pub trait DataSource {
+    /// Every club, with its crews.
+    fn clubs(&mut self) -> Result<Clubs, BumpsError>;
+
+    /// Every crew, in club order.
+    fn crews(&mut self) -> Result<Vec<Crew>, BumpsError> {
+        Ok(self.clubs()?.crews().into_iter().cloned().collect())
+    }
+
+    /// The days and divisions of every year of `competition` in `years` (or
+    /// every year, if it's `None`).
+    fn competition_years(
+        &mut self,
+        competition: Competition,
+        years: Option<&RangeInclusive<u32>>,
+    ) -> Result<BTreeMap<u32, YearChart>, BumpsError>;
+
+    /// Every crew's per-day positions in `competition`, for the years in
+    /// `years` (or every year, if it's `None`).
+    fn positions(
+        &mut self,
+        competition: Competition,
+        years: Option<&RangeInclusive<u32>>,
+    ) -> Result<Vec<CrewRecord>, BumpsError>;
+
+    /// One crew's per-day positions, with no years if it didn't compete in
+    /// `years`, or `None` if there's no such crew. Sources that can look up a
+    /// single crew cheaply should override this.
+    fn crew(
+        &mut self,
+        competition: Competition,
+        alias: &str,
+        years: Option<&RangeInclusive<u32>>,
+    ) -> Result<Option<CrewRecord>, BumpsError> {
+        let Some(crew) = self.crews()?.into_iter().find(|x| x.alias == alias) else {
+            return Ok(None);
+        };
+
+        let record = self
+            .positions(competition, years)?
+            .into_iter()
+            .find(|x| x.alias == alias)
+            .unwrap_or_else(|| CrewRecord::new(crew.name, crew.alias, BTreeMap::new()));
+
+        Ok(Some(record))
+    }
+}
+
+/// The data files from the CD-ROM.
+pub struct CdRom {
+    data_dir: PathBuf,
+    clubs: Option<Clubs>,
+    // Every chart, by competition, since the positions can't be read without
+    // knowing how many days each year had.
+    charts: HashMap<Competition, BTreeMap<u32, YearChart>>,
+}
+
+impl CdRom {
+    pub fn new(data_dir: &Path) -> Self {
+        CdRom {
+            data_dir: data_dir.to_path_buf(),
+            clubs: None,
+            charts: HashMap::new(),
+        }
+    }
+
+    fn load_clubs(&mut self) -> Result<&Clubs, BumpsError> {
+        if self.clubs.is_none() {
+            self.clubs = Some(dataset::load_clubs(&dataset::colleges_path(
+                &self.data_dir,
+            ))?);
+        }
+
+        Ok(self.clubs.as_ref().unwrap())
+    }
+
+    fn load_charts(
+        &mut self,
+        competition: Competition,
+    ) -> Result<&BTreeMap<u32, YearChart>, BumpsError> {
+        if !self.charts.contains_key(&competition) {
+            let rows = rw2::Row::from_file(&dataset::rw2_path(&self.data_dir, competition))?;
+
+            let min_year = rows.iter().filter_map(|x| x.start_year()).min();
+            let max_year = rows.iter().filter_map(|x| x.end_year()).max();
+
+            let charts = match (min_year, max_year) {
+                (Some(min_year), Some(max_year)) => (min_year..=max_year)
+                    .map(|year| {
+                        let path = dataset::chart_path(&self.data_dir, competition, year);
+
+                        Ok((year, dataset::load_chart(&path)?))
+                    })
+                    .collect::<Result<BTreeMap<u32, YearChart>, BumpsError>>()?,
+                _ => BTreeMap::new(),
+            };
+
+            self.charts.insert(competition, charts);
+        }
+
+        Ok(&self.charts[&competition])
+    }
+}
+
+impl DataSource for CdRom {
+    fn clubs(&mut self) -> Result<Clubs, BumpsError> {
+        self.load_clubs().cloned()
+    }
+
+    fn competition_years(
+        &mut self,
+        competition: Competition,
+        years: Option<&RangeInclusive<u32>>,
+    ) -> Result<BTreeMap<u32, YearChart>, BumpsError> {
+        let mut charts = self.load_charts(competition)?.clone();
+
+        if let Some(years) = years {
+            charts.retain(|year, _| years.contains(year));
+        }
+
+        Ok(charts)
+    }
+
+    fn positions(
+        &mut self,
+        competition: Competition,
+        years: Option<&RangeInclusive<u32>>,
+    ) -> Result<Vec<CrewRecord>, BumpsError> {
+        let rw2_path = dataset::rw2_path(&self.data_dir, competition);
+        let raw_path = dataset::raw_path(&self.data_dir, competition);
+
+        self.load_clubs()?;
+        self.load_charts(competition)?;
+
+        let crews = self.clubs.as_ref().unwrap().crews();
+        let charts = &self.charts[&competition];
+
+        let rows = rw2::Row::from_file(&rw2_path)?;
+        let mut raw = raw::Raw::load(&raw_path)?;
+
+        let (records, issues) =
+            slice_positions(&crews, &rows, charts, &mut raw, &rw2_path, &raw_path, years);
+
+        match issues.into_iter().find(Issue::is_fatal) {
+            Some(issue) => Err(issue.into_error()),
+            None => Ok(records),
+        }
+    }
+}
+
+/// A problem found while lining up a competition's .rw2 index, .raw file and
+/// charts.
+#[derive(Debug)]
+pub enum Issue {
+    /// A file couldn't be read.
+    Load(BumpsError),
+    /// The files don't line up, for `crew` or, if it's `None`, as a whole.
+    /// `record` is the .rw2 record (from 0) concerned.
+    Mismatch {
+        file: PathBuf,
+        record: usize,
+        crew: Option<String>,
+        message: String,
+        // Whether it stopped the crew's positions being read in full, rather
+        // than just being inconsistent.
+        fatal: bool,
+    },
+}
+
+impl Issue {
+    pub fn is_fatal(&self) -> bool {
+        match self {
+            Self::Load(_) => true,
+            Self::Mismatch { fatal, .. } => *fatal,
+        }
+    }
+
+    pub fn into_error(self) -> BumpsError {
+        match self {
+            Self::Load(err) => err,
+            Self::Mismatch {
+                file,
+                record,
+                crew,
+                message,
+                ..
+            } => BumpsError::Rw2 {
+                path: file,
+                record,
+                message: match crew {
+                    Some(crew) => format!("{crew}: {message}"),
+                    None => message,
+                },
+            },
+        }
+    }
+}
+
+/// Slice every crew's positions out of `raw`, for the years in `years` (or
+/// every year, if it's `None`). `rows` holds each crew's .rw2 record, in the
+/// same order as `crews`, and `charts` gives each year's number of days. This
+/// carries on past problems, returning them alongside the records, so that
+/// both the loader and `validate` can share it.
+pub fn slice_positions(
+    crews: &[&Crew],
+    rows: &[rw2::Row],
+    charts: &BTreeMap<u32, YearChart>,
+    raw: &mut raw::Raw,
+    rw2_path: &Path,
+    raw_path: &Path,
+    years: Option<&RangeInclusive<u32>>,
+) -> (Vec<CrewRecord>, Vec<Issue>) {
+    let mut records = vec![];
+    let mut issues = vec![];
+
+    let mismatch =
+        |file: &Path, record: usize, crew: Option<&str>, message: String, fatal| Issue::Mismatch {
+            file: file.to_path_buf(),
+            record,
+            crew: crew.map(String::from),
+            message,
+            fatal,
+        };
+
+    if rows.len() != crews.len() {
+        issues.push(mismatch(
+            rw2_path,
+            rows.len().min(crews.len()),
+            None,
+            format!(
+                "{} records, but College.dat lists {} crews",
+                rows.len(),
+                crews.len()
+            ),
+            true,
+        ));
+    }
+
+    let raw_size = match raw.size() {
+        Ok(size) => size,
+        Err(err) => {
+            issues.push(Issue::Load(err));
+            return (records, issues);
+        }
+    };
+
+    for (record, (row, crew)) in rows.iter().zip(crews).enumerate() {
+        let (Some(start_year), Some(end_year), Some(start_idx), Some(end_idx)) = (
+            row.start_year(),
+            row.end_year(),
+            row.start_idx(),
+            row.end_idx(),
+        ) else {
+            continue;
+        };
+
+        let alias = Some(crew.alias.as_str());
+        let mut positions = BTreeMap::new();
+        let mut idx = start_idx;
+
+        for year in start_year..=end_year {
+            let Some(chart) = charts.get(&year) else {
+                issues.push(mismatch(
+                    rw2_path,
+                    record,
+                    alias,
+                    format!("competed in {year}, which has no usable chart file"),
+                    true,
+                ));
+                break;
+            };
+
+            // The index comes from the .rw2 file, so mustn't be trusted not
+            // to overflow.
+            let Some(next_idx) = idx.checked_add(chart.days as u32 + 1) else {
+                issues.push(mismatch(
+                    rw2_path,
+                    record,
+                    alias,
+                    format!("{year} starts at raw byte {idx}, so its range overflows"),
+                    true,
+                ));
+                break;
+            };
+
+            if next_idx - 1 > end_idx {
+                issues.push(mismatch(
+                    rw2_path,
+                    record,
+                    alias,
+                    format!(
+                        "{year} needs raw bytes {idx}..{next_idx}, \
+                         past the end of its range {start_idx}..={end_idx}"
+                    ),
+                    true,
+                ));
+                break;
+            }
+
+            if next_idx as u64 > raw_size {
+                issues.push(mismatch(
+                    raw_path,
+                    record,
+                    alias,
+                    format!(
+                        "{year} needs raw bytes {idx}..{next_idx}, \
+                         but the file is only {raw_size} bytes long"
+                    ),
+                    true,
+                ));
+                break;
+            }
+
+            if years.is_none_or(|years| years.contains(&year)) {
+                match raw.range(idx, next_idx) {
+                    Ok(day_positions) if day_positions.iter().all(|x| *x == 0) => {}
+                    Ok(day_positions) => {
+                        positions.insert(year, day_positions);
+                    }
+                    Err(err) => {
+                        issues.push(Issue::Load(err));
+                        break;
+                    }
+                }
+            }
+
+            idx = next_idx;
+
+            if year == end_year && end_idx.checked_add(1) != Some(idx) {
+                issues.push(mismatch(
+                    rw2_path,
+                    record,
+                    alias,
+                    format!(
+                        "range {start_idx}..={end_idx} doesn't match the chart day counts, \
+                         which end at {}",
+                        idx - 1
+                    ),
+                    false,
+                ));
+            }
+        }
+
+        records.push(CrewRecord::new(
+            crew.name.clone(),
+            crew.alias.clone(),
+            positions,
+        ));
+    }
+
+    (records, issues)
+}
+
+/// Every difference between `a` and `b`, described in words, e.g. to check
+/// that a database matches the CD-ROM files it was built from.
+pub fn differences(a: &Dataset, b: &Dataset) -> Vec<String> {
+    let mut differences = vec![];
+
+    let crews = |dataset: &Dataset| -> BTreeMap<String, (String, String)> {
+        dataset
+            .clubs()
+            .clubs()
+            .iter()
+            .flat_map(|club| {
+                club.crews
+                    .iter()
+                    .map(move |crew| (crew.alias.clone(), (crew.name.clone(), club.name.clone())))
+            })
+            .collect()
+    };
+
+    let (a_crews, b_crews) = (crews(a), crews(b));
+
+    for (alias, x) in &a_crews {
+        match b_crews.get(alias) {
+            None => differences.push(format!("crew {alias}: only in the first source")),
+            Some(y) if x != y => differences.push(format!(
+                "crew {alias}: {} of {} vs {} of {}",
+                x.0, x.1, y.0, y.1
+            )),
+            Some(_) => {}
+        }
+    }
+
+    for alias in b_crews.keys() {
+        if !a_crews.contains_key(alias) {
+            differences.push(format!("crew {alias}: only in the second source"));
+        }
+    }
+
+    let colours = |dataset: &Dataset| -> BTreeMap<String, (u8, u8, u8)> {
+        dataset
+            .clubs()
+            .clubs()
+            .iter()
+            .map(|club| (club.name.clone(), club.colour))
+            .collect()
+    };
+
+    let (a_clubs, b_clubs) = (colours(a), colours(b));
+
+    for (name, x) in &a_clubs {
+        match b_clubs.get(name) {
+            None => differences.push(format!("club {name}: only in the first source")),
+            Some(y) if x != y => differences.push(format!("club {name}: colour {x:?} vs {y:?}")),
+            Some(_) => {}
+        }
+    }
+
+    for name in b_clubs.keys() {
+        if !a_clubs.contains_key(name) {
+            differences.push(format!("club {name}: only in the second source"));
+        }
+    }
+
+    let mut competitions: Vec<Competition> = a.competitions().chain(b.competitions()).collect();
+    competitions.sort();
+    competitions.dedup();
+
+    for competition in competitions {
+        let mut years: Vec<u32> = a.years(competition).chain(b.years(competition)).collect();
+        years.sort_unstable();
+        years.dedup();
+
+        for year in years {
+            let context = format!("{competition} {year}");
+
+            match (a.chart(competition, year), b.chart(competition, year)) {
+                (Some(x), Some(y)) => {
+                    if x.days != y.days {
+                        differences.push(format!("{context}: {} vs {} days", x.days, y.days));
+                    }
+
+                    if x.crews_per_division != y.crews_per_division {
+                        differences.push(format!(
+                            "{context}: divisions {:?} vs {:?}",
+                            x.crews_per_division, y.crews_per_division
+                        ));
+                    }
+                }
+                (Some(_), None) => {
+                    differences.push(format!("{context}: only in the first source"));
+                    continue;
+                }
+                (None, Some(_)) => {
+                    differences.push(format!("{context}: only in the second source"));
+                    continue;
+                }
+                (None, None) => continue,
+            }
+
+            // Crews in only one source have already been reported.
+            for alias in a_crews.keys().filter(|x| b_crews.contains_key(*x)) {
+                let x = a.crew(competition, alias).and_then(|crew| crew.year(year));
+                let y = b.crew(competition, alias).and_then(|crew| crew.year(year));
+
+                if x != y {
+                    differences.push(format!("{context}: {alias}: {x:?} vs {y:?}"));
+                }
+            }
+        }
+    }
+
+    differences
+}
+
+#[cfg(test)]
+pub(crate) mod testing {
+    use std::collections::BTreeMap;
+    use std::ops::RangeInclusive;
+
+    use super::DataSource;
+    use crate::colleges::{Club, Clubs, Crew};
+    use crate::competition::Competition;
+    use crate::crew::CrewRecord;
+    use crate::dataset::Dataset;
+    use crate::error::BumpsError;
+    use crate::year::YearChart;
+
+    // Hand-written results, for building datasets in tests.
+    pub(crate) struct Memory {
+        clubs: Clubs,
+        charts: BTreeMap<u32, YearChart>,
+        crews: Vec<CrewRecord>,
+    }
+
+    impl DataSource for Memory {
+        fn clubs(&mut self) -> Result<Clubs, BumpsError> {
+            Ok(self.clubs.clone())
+        }
+
+        fn competition_years(
+            &mut self,
+            _competition: Competition,
+            years: Option<&RangeInclusive<u32>>,
+        ) -> Result<BTreeMap<u32, YearChart>, BumpsError> {
+            let mut charts = self.charts.clone();
+            charts.retain(|year, _| years.is_none_or(|years| years.contains(year)));
+            Ok(charts)
+        }
+
+        fn positions(
+            &mut self,
+            _competition: Competition,
+            years: Option<&RangeInclusive<u32>>,
+        ) -> Result<Vec<CrewRecord>, BumpsError> {
+            let mut crews = self.crews.clone();
+
+            for crew in &mut crews {
+                crew.years
+                    .retain(|year, _| years.is_none_or(|years| years.contains(year)));
+            }
+
+            Ok(crews)
+        }
+    }
+
+    /// A dataset with one competition, `years` as (year, days, DIVS), and
+    /// `crews` as (club, alias, year, positions). Crews are named after their
+    /// aliases.
+    pub(crate) fn dataset(
+        competition: Competition,
+        years: &[(u32, u8, &[u8])],
+        crews: &[(&str, &str, u32, &[u8])],
+    ) -> Dataset {
+        let mut clubs: Vec<Club> = vec![];
+        let mut records: Vec<CrewRecord> = vec![];
+
+        for (club, alias, year, positions) in crews {
+            if !clubs.iter().any(|x| x.name == *club) {
+                clubs.push(Club {
+                    name: club.to_string(),
+                    colour: (0, 0, 0),
+                    crews: vec![],
+                });
+            }
+
+            let club = clubs.iter_mut().find(|x| x.name == *club).unwrap();
+
+            if !club.crews.iter().any(|x| x.alias == *alias) {
+                club.crews.push(Crew {
+                    name: alias.to_string(),
+                    alias: alias.to_string(),
+                });
+                records.push(CrewRecord::new(
+                    alias.to_string(),
+                    alias.to_string(),
+                    BTreeMap::new(),
+                ));
+            }
+
+            let record = records.iter_mut().find(|x| x.alias == *alias).unwrap();
+            record.years.insert(*year, positions.to_vec());
+        }
+
+        let mut source = Memory {
+            clubs: Clubs::new(clubs),
+            charts: years
+                .iter()
+                .map(|(year, days, divs)| (*year, YearChart::new(*days, divs.to_vec())))
+                .collect(),
+            crews: records,
+        };
+
+        Dataset::from_source(&mut source, &[competition], None).unwrap()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::testing::dataset;
+    use super::{differences, slice_positions, Issue};
+    use crate::colleges::Crew;
+    use crate::competition::Competition;
+    use crate::raw::Raw;
+    use crate::rw2::Row;
+    use crate::year::YearChart;
+
+    const MAYS: Competition = Competition::MenMays;
+
+    #[test]
+    fn same() {
+        let a = dataset(
+            MAYS,
+            &[(2000, 1, &[2])],
+            &[("A", "a1", 2000, &[1, 1]), ("B", "b1", 2000, &[2, 2])],
+        );
+
+        assert!(differences(&a, &a.clone()).is_empty());
+    }
+
+    #[test]
+    fn changed_position() {
+        let a = dataset(
+            MAYS,
+            &[(2000, 1, &[2])],
+            &[("A", "a1", 2000, &[1, 1]), ("B", "b1", 2000, &[2, 2])],
+        );
+        let b = dataset(
+            MAYS,
+            &[(2000, 1, &[2])],
+            &[("A", "a1", 2000, &[1, 1]), ("B", "b1", 2000, &[2, 3])],
+        );
+
+        assert_eq!(
+            differences(&a, &b),
+            ["men's may bumps 2000: b1: Some([2, 2]) vs Some([2, 3])"]
+        );
+    }
+
+    #[test]
+    fn extra_crew() {
+        let a = dataset(MAYS, &[(2000, 1, &[2])], &[("A", "a1", 2000, &[1, 1])]);
+        let b = dataset(
+            MAYS,
+            &[(2000, 1, &[2])],
+            &[("A", "a1", 2000, &[1, 1]), ("A", "a2", 2000, &[2, 2])],
+        );
+
+        assert_eq!(differences(&a, &b), ["crew a2: only in the second source"]);
+        assert_eq!(differences(&b, &a), ["crew a2: only in the first source"]);
+    }
+
+    #[test]
+    fn extra_club() {
+        let a = dataset(MAYS, &[(2000, 1, &[2])], &[("A", "a1", 2000, &[1, 1])]);
+        let b = dataset(
+            MAYS,
+            &[(2000, 1, &[2])],
+            &[("A", "a1", 2000, &[1, 1]), ("B", "b1", 2000, &[2, 2])],
+        );
+
+        assert_eq!(
+            differences(&a, &b),
+            [
+                "crew b1: only in the second source",
+                "club B: only in the second source"
+            ]
+        );
+        assert_eq!(
+            differences(&b, &a),
+            [
+                "crew b1: only in the first source",
+                "club B: only in the first source"
+            ]
+        );
+    }
+
+    #[test]
+    fn changed_divisions() {
+        let a = dataset(MAYS, &[(2000, 1, &[2])], &[("A", "a1", 2000, &[1, 1])]);
+        let b = dataset(MAYS, &[(2000, 1, &[1, 1])], &[("A", "a1", 2000, &[1, 1])]);
+
+        assert_eq!(
+            differences(&a, &b),
+            ["men's may bumps 2000: divisions [2] vs [1, 1]"]
+        );
+    }
+
+    #[test]
+    fn year_in_one_source() {
+        let a = dataset(MAYS, &[(2000, 1, &[1])], &[("A", "a1", 2000, &[1, 1])]);
+        let b = dataset(
+            MAYS,
+            &[(2000, 1, &[1]), (2001, 1, &[1])],
+            &[("A", "a1", 2000, &[1, 1]), ("A", "a1", 2001, &[1, 1])],
+        );
+
+        assert_eq!(
+            differences(&a, &b),
+            ["men's may bumps 2001: only in the second source"]
+        );
+    }
+
+    #[test]
+    fn index_overflow() {
+        // One crew, racing in 2000 from raw byte u32::MAX - 1.
+        let record: Vec<u8> = [0, 0, 2000, 2000, u32::MAX - 1, u32::MAX]
+            .iter()
+            .flat_map(|x: &u32| x.to_le_bytes())
+            .collect();
+
+        let dir = std::env::temp_dir().join(format!("the-bumps-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let (rw2_path, raw_path) = (dir.join("overflow.rw2"), dir.join("overflow.raw"));
+        std::fs::write(&rw2_path, record).unwrap();
+        std::fs::write(&raw_path, [1, 1, 1]).unwrap();
+
+        let rows = Row::from_file(&rw2_path).unwrap();
+        let mut raw = Raw::load(&raw_path).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        let crew = Crew {
+            name: String::from("a1"),
+            alias: String::from("a1"),
+        };
+        let charts = BTreeMap::from([(2000, YearChart::new(4, vec![1]))]);
+
+        let (records, issues) = slice_positions(
+            &[&crew],
+            &rows,
+            &charts,
+            &mut raw,
+            &rw2_path,
+            &raw_path,
+            None,
+        );
+
+        assert_eq!(records.len(), 1);
+        assert!(records[0].years.is_empty());
+        assert!(matches!(
+            issues.as_slice(),
+            [Issue::Mismatch { record: 0, fatal: true, message, .. }] if message.contains("overflows")
+        ));
+    }
+}
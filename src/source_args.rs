@@ -0,0 +1,59 @@
+use std::{
+    error::Error,
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+};
+
+use clap::Parser;
+
+use the_bumps::db::DbSource;
+use the_bumps::source::CdRom;
+use the_bumps::{Competition, DataSource, Dataset};
+
+// Where a command reads its results from: the CD-ROM files, or a database
+// built from them by `db from-bumps-cd-rom`.
+#[derive(Parser, Debug)]
+pub(crate) struct SourceArgs {
+    #[arg(long, required_unless_present = "db", conflicts_with = "db")]
+    data_dir: Option<PathBuf>,
+    /// Read the results from a database built by `db from-bumps-cd-rom`,
+    /// rather than the CD-ROM files.
+    #[arg(long)]
+    db: Option<PathBuf>,
+}
+
+impl SourceArgs {
+    pub(crate) fn new(data_dir: Option<PathBuf>, db: Option<PathBuf>) -> Self {
+        SourceArgs { data_dir, db }
+    }
+
+    pub(crate) fn open(&self) -> Result<Box<dyn DataSource>, Box<dyn Error>> {
+        match (&self.db, &self.data_dir) {
+            (Some(db), _) => Ok(Box::new(DbSource::open(db)?)),
+            (None, Some(data_dir)) => Ok(Box::new(CdRom::new(data_dir))),
+            (None, None) => Err("Pass either --data-dir or --db".into()),
+        }
+    }
+
+    /// The database or data directory, e.g. for messages.
+    pub(crate) fn path(&self) -> &Path {
+        self.db
+            .as_deref()
+            .or(self.data_dir.as_deref())
+            .unwrap_or(Path::new(""))
+    }
+
+    /// Load `competitions`, keeping only the years in `years` (or every year,
+    /// if it's `None`).
+    pub(crate) fn load(
+        &self,
+        competitions: &[Competition],
+        years: Option<RangeInclusive<u32>>,
+    ) -> Result<Dataset, Box<dyn Error>> {
+        Ok(Dataset::from_source(
+            self.open()?.as_mut(),
+            competitions,
+            years,
+        )?)
+    }
+}
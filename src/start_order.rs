@@ -0,0 +1,223 @@
+// Works out next year's start order from this year's finishing order. Crews
+// that aren't returning are dropped and the crews below them move up, then
+// the bottom crews fall off (per `Rules`), and finally the crews getting on
+// are added at the bottom, in the order they qualified.
+
+use crate::competition::Competition;
+use crate::dataset::Dataset;
+
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct Rules {
+    // How many of the lowest finishers fall off, and have to requalify.
+    pub fall_off: usize,
+    // The most crews allowed on the river. Any more than this, counting from
+    // the bottom after the crews getting on are added, fall off too.
+    pub max_crews: Option<usize>,
+    // Division sizes, head first. If empty, this year's divisions are kept,
+    // with the bottom division growing or shrinking to fit.
+    pub crews_per_division: Vec<u8>,
+}
+
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct StartOrder {
+    pub crews: Vec<String>, // aliases, head first
+    pub crews_per_division: Vec<u8>,
+    // Crews that fell off the bottom.
+    pub fallen_off: Vec<String>,
+}
+
+impl StartOrder {
+    /// The crews in each division, head first. Any crews past the end of
+    /// `crews_per_division` go in the last division.
+    pub fn divisions(&self) -> Vec<&[String]> {
+        let mut divisions = vec![];
+        let mut head = 0;
+
+        for size in &self.crews_per_division {
+            let end = (head + *size as usize).min(self.crews.len());
+            divisions.push(head..end);
+            head = end;
+        }
+
+        match divisions.last_mut() {
+            Some(last) => last.end = self.crews.len(),
+            None if !self.crews.is_empty() => divisions.push(0..self.crews.len()),
+            None => {}
+        }
+
+        divisions
+            .into_iter()
+            .map(|range| &self.crews[range])
+            .collect()
+    }
+
+    /// The number of crews the divisions have room for.
+    pub fn places(&self) -> usize {
+        self.crews_per_division.iter().map(|x| *x as usize).sum()
+    }
+}
+
+/// Next year's start order for `competition`, from the final positions in
+/// `year`, or `None` if there are no results for that year.
+pub fn next_year(
+    dataset: &Dataset,
+    competition: Competition,
+    year: u32,
+    leaving: &[String],
+    getting_on: &[String],
+    rules: &Rules,
+) -> Option<StartOrder> {
+    let chart = dataset.chart(competition, year)?;
+
+    let mut finishes: Vec<(u8, &str)> = dataset
+        .crews_in(competition, year)
+        .filter_map(|crew| {
+            let finish = crew.year(year)?.iter().rev().copied().find(|x| *x != 0)?;
+            Some((finish, crew.alias.as_str()))
+        })
+        .collect();
+
+    if finishes.is_empty() {
+        return None;
+    }
+
+    finishes.sort_unstable();
+
+    let mut crews: Vec<String> = finishes
+        .into_iter()
+        .map(|(_, alias)| String::from(alias))
+        .filter(|alias| !leaving.contains(alias))
+        .collect();
+
+    let mut fallen_off = crews.split_off(crews.len().saturating_sub(rules.fall_off));
+
+    // A crew that fell off but requalified is getting on again, so it hasn't
+    // fallen off after all.
+    for alias in getting_on {
+        if !crews.contains(alias) {
+            fallen_off.retain(|x| x != alias);
+            crews.push(alias.clone());
+        }
+    }
+
+    if let Some(max_crews) = rules.max_crews {
+        if crews.len() > max_crews {
+            let mut over = crews.split_off(max_crews);
+            over.append(&mut fallen_off);
+            fallen_off = over;
+        }
+    }
+
+    let crews_per_division = if rules.crews_per_division.is_empty() {
+        let mut sizes = chart.crews_per_division.clone();
+        let others: usize = sizes.iter().rev().skip(1).map(|x| *x as usize).sum();
+
+        if let Some(last) = sizes.last_mut() {
+            *last = crews.len().saturating_sub(others).min(u8::MAX as usize) as u8;
+        }
+
+        sizes
+    } else {
+        rules.crews_per_division.clone()
+    };
+
+    Some(StartOrder {
+        crews,
+        crews_per_division,
+        fallen_off,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{next_year, Rules, StartOrder};
+    use crate::competition::Competition;
+    use crate::source::testing::dataset;
+
+    fn aliases(division: &[String]) -> Vec<&str> {
+        division.iter().map(String::as_str).collect()
+    }
+
+    #[test]
+    fn divisions_keep_every_crew() {
+        let order = StartOrder {
+            crews: ["a", "b", "c", "d"].map(String::from).to_vec(),
+            crews_per_division: vec![2, 1],
+            fallen_off: vec![],
+        };
+
+        let divisions = order.divisions();
+
+        assert_eq!(order.places(), 3);
+        assert_eq!(divisions.len(), 2);
+        assert_eq!(aliases(divisions[0]), ["a", "b"]);
+        assert_eq!(aliases(divisions[1]), ["c", "d"]);
+    }
+
+    #[test]
+    fn bottom_division_resized_to_fit() {
+        let competition = Competition::MenMays;
+        let dataset = dataset(
+            competition,
+            &[(2000, 1, &[2, 2])],
+            &[
+                ("A", "a1", 2000, &[1, 1]),
+                ("A", "a2", 2000, &[2, 3]),
+                ("B", "b1", 2000, &[3, 2]),
+                ("B", "b2", 2000, &[4, 4]),
+            ],
+        );
+
+        let rules = Rules {
+            fall_off: 1,
+            ..Rules::default()
+        };
+
+        let order = next_year(
+            &dataset,
+            competition,
+            2000,
+            &[],
+            &[String::from("c1"), String::from("c2")],
+            &rules,
+        )
+        .unwrap();
+
+        assert_eq!(order.crews, ["a1", "b1", "a2", "c1", "c2"]);
+        assert_eq!(order.crews_per_division, [2, 3]);
+        assert_eq!(order.fallen_off, ["b2"]);
+        assert_eq!(order.places(), order.crews.len());
+    }
+
+    #[test]
+    fn fallen_off_crews_can_get_back_on() {
+        let competition = Competition::MenMays;
+        let dataset = dataset(
+            competition,
+            &[(2000, 1, &[3])],
+            &[
+                ("A", "a1", 2000, &[1, 1]),
+                ("A", "a2", 2000, &[2, 2]),
+                ("B", "b1", 2000, &[3, 3]),
+            ],
+        );
+
+        let rules = Rules {
+            fall_off: 2,
+            ..Rules::default()
+        };
+
+        let order = next_year(
+            &dataset,
+            competition,
+            2000,
+            &[],
+            &[String::from("c1"), String::from("b1")],
+            &rules,
+        )
+        .unwrap();
+
+        assert_eq!(order.crews, ["a1", "c1", "b1"]);
+        assert_eq!(order.fallen_off, ["a2"]);
+    }
+}
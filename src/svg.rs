@@ -0,0 +1,420 @@
+// Renders bumps charts as SVG.
+//
+// The classic bumps chart has one line per crew, running left to right
+// across the days of the competition, with the head of the river at the top.
+// Each bump shows up as two lines crossing.
+//
+// History charts follow crews over many years instead, with each year's days
+// laid out one after another.
+
+use std::fmt::Write;
+use std::ops::RangeInclusive;
+
+use crate::competition::Competition;
+use crate::dataset::Dataset;
+use crate::events::Events;
+
+const ROW_HEIGHT: f64 = 16.0;
+const DAY_WIDTH: f64 = 64.0;
+const NAME_WIDTH: f64 = 160.0;
+const HEADER_HEIGHT: f64 = 48.0;
+const MARGIN: f64 = 8.0;
+
+const HISTORY_ROW_HEIGHT: f64 = 6.0;
+const HISTORY_DAY_WIDTH: f64 = 12.0;
+const HISTORY_YEAR_GAP: f64 = 12.0;
+const LEGEND_ROW_HEIGHT: f64 = 14.0;
+
+pub fn hex_colour((r, g, b): (u8, u8, u8)) -> String {
+    format!("#{r:02x}{g:02x}{b:02x}")
+}
+
+pub(crate) fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// A minimal SVG document builder.
+pub(crate) struct Svg(String);
+
+impl Svg {
+    pub(crate) fn new(width: f64, height: f64) -> Self {
+        let mut svg = String::new();
+
+        writeln!(
+            svg,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}" font-family="sans-serif" font-size="11">"#
+        )
+        .unwrap();
+        writeln!(
+            svg,
+            r#"<rect width="{width}" height="{height}" fill="white"/>"#
+        )
+        .unwrap();
+
+        Svg(svg)
+    }
+
+    pub(crate) fn line(&mut self, (x1, y1): (f64, f64), (x2, y2): (f64, f64), style: &str) {
+        writeln!(
+            self.0,
+            r#"<line x1="{x1:.1}" y1="{y1:.1}" x2="{x2:.1}" y2="{y2:.1}" {style}/>"#
+        )
+        .unwrap();
+    }
+
+    pub(crate) fn rect(&mut self, (x, y): (f64, f64), (width, height): (f64, f64), style: &str) {
+        writeln!(
+            self.0,
+            r#"<rect x="{x:.1}" y="{y:.1}" width="{width:.1}" height="{height:.1}" {style}/>"#
+        )
+        .unwrap();
+    }
+
+    pub(crate) fn polyline(&mut self, points: &[(f64, f64)], style: &str) {
+        let points: Vec<String> = points
+            .iter()
+            .map(|(x, y)| format!("{x:.1},{y:.1}"))
+            .collect();
+
+        writeln!(
+            self.0,
+            r#"<polyline points="{}" fill="none" {style}/>"#,
+            points.join(" ")
+        )
+        .unwrap();
+    }
+
+    pub(crate) fn circle(&mut self, (cx, cy): (f64, f64), r: f64, style: &str) {
+        writeln!(
+            self.0,
+            r#"<circle cx="{cx:.1}" cy="{cy:.1}" r="{r:.1}" {style}/>"#
+        )
+        .unwrap();
+    }
+
+    pub(crate) fn text(&mut self, (x, y): (f64, f64), anchor: &str, text: &str, style: &str) {
+        writeln!(
+            self.0,
+            r#"<text x="{x:.1}" y="{y:.1}" text-anchor="{anchor}" dominant-baseline="middle" {style}>{}</text>"#,
+            escape(text)
+        )
+        .unwrap();
+    }
+
+    pub(crate) fn finish(mut self) -> String {
+        self.0.push_str("</svg>\n");
+        self.0
+    }
+}
+
+/// Draw the bumps chart for `competition` in `year`, or `None` if there's no
+/// data for that year.
+pub fn bumps_chart(dataset: &Dataset, competition: Competition, year: u32) -> Option<String> {
+    let chart = dataset.chart(competition, year)?;
+    let crews: Vec<_> = dataset.crews_in(competition, year).collect();
+
+    if crews.is_empty() {
+        return None;
+    }
+
+    let days = chart.days as f64;
+    let rows = crews
+        .iter()
+        .filter_map(|crew| crew.year(year)?.iter().max())
+        .max()
+        .copied()
+        .unwrap_or(0) as f64;
+
+    let left = MARGIN + NAME_WIDTH;
+    let right = left + days * DAY_WIDTH;
+    let width = right + NAME_WIDTH + MARGIN;
+    let height = HEADER_HEIGHT + rows * ROW_HEIGHT + MARGIN;
+
+    let x = |day: usize| left + day as f64 * DAY_WIDTH;
+    let y = |position: u8| HEADER_HEIGHT + (position as f64 - 0.5) * ROW_HEIGHT;
+
+    let mut svg = Svg::new(width, height);
+
+    svg.text(
+        (width / 2.0, MARGIN + 8.0),
+        "middle",
+        &format!("{competition} {year}"),
+        r#"font-size="14" font-weight="bold""#,
+    );
+
+    for day in 1..=chart.days as usize {
+        svg.text(
+            ((x(day - 1) + x(day)) / 2.0, HEADER_HEIGHT - 12.0),
+            "middle",
+            &format!("Day {day}"),
+            "",
+        );
+    }
+
+    for day in 0..=chart.days as usize {
+        svg.line(
+            (x(day), HEADER_HEIGHT),
+            (x(day), height - MARGIN),
+            r##"stroke="#dddddd""##,
+        );
+    }
+
+    // Division separators sit between the bottom crew of one division and the
+    // head of the next.
+    let mut bottom = 0u32;
+
+    for (i, size) in chart.crews_per_division.iter().enumerate() {
+        let top = HEADER_HEIGHT + bottom as f64 * ROW_HEIGHT;
+
+        if i > 0 {
+            svg.line(
+                (MARGIN, top),
+                (width - MARGIN, top),
+                r#"stroke="black" stroke-dasharray="4 2""#,
+            );
+        }
+
+        svg.text(
+            (MARGIN, top + 6.0),
+            "start",
+            &format!("Division {}", i + 1),
+            r##"font-size="8" fill="#888888""##,
+        );
+
+        bottom += *size as u32;
+    }
+
+    for crew in &crews {
+        let Some(positions) = crew.year(year) else {
+            continue;
+        };
+
+        let colour = dataset
+            .club_of(&crew.alias)
+            .map(|club| hex_colour(club.colour))
+            .unwrap_or_else(|| String::from("black"));
+
+        let points: Vec<(f64, f64)> = positions
+            .iter()
+            .enumerate()
+            .filter(|(_, position)| **position != 0)
+            .map(|(day, position)| (x(day), y(*position)))
+            .collect();
+
+        svg.polyline(
+            &points,
+            &format!(r#"stroke="{colour}" stroke-width="2" stroke-linejoin="round""#),
+        );
+
+        if let (Some(start), Some(finish)) = (positions.first(), positions.last()) {
+            svg.text((left - 6.0, y(*start)), "end", &crew.name, "");
+            svg.text((right + 6.0, y(*finish)), "start", &crew.name, "");
+        }
+    }
+
+    // Mark where the bumping crew catches the crew it bumped: halfway through
+    // the day, where the two lines cross.
+    for event in Events::infer(crews.iter().copied(), year).iter() {
+        if event.outcome.is_bump() {
+            let day = event.day as usize;
+            let crossing = (
+                (x(day - 1) + x(day)) / 2.0,
+                (y(event.start) + y(event.finish)) / 2.0,
+            );
+
+            svg.circle(crossing, 2.5, r#"fill="black""#);
+        }
+    }
+
+    Some(svg.finish())
+}
+
+/// Wrap an SVG document in a standalone HTML page.
+pub fn html_page(title: &str, svg: &str) -> String {
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n</head>\n<body>\n{svg}</body>\n</html>\n",
+        escape(title)
+    )
+}
+
+/// Draw the positions of each `(competition, crew alias)` in `series` over
+/// `years`, or `None` if none of them competed in that time.
+///
+/// The divisions shaded in the background are those of the first competition
+/// in `series` that has a chart for the year.
+pub fn history_chart(
+    dataset: &Dataset,
+    series: &[(Competition, &str)],
+    years: RangeInclusive<u32>,
+) -> Option<String> {
+    // The x offset and number of days of every year shown.
+    let mut blocks = vec![];
+    let mut width = MARGIN + NAME_WIDTH / 2.0;
+
+    for year in years {
+        let days = series
+            .iter()
+            .filter_map(|(competition, _)| Some(dataset.chart(*competition, year)?.days))
+            .max();
+
+        if let Some(days) = days {
+            blocks.push((year, width, days));
+            width += days as f64 * HISTORY_DAY_WIDTH + HISTORY_YEAR_GAP;
+        }
+    }
+
+    let crews: Vec<_> = series
+        .iter()
+        .enumerate()
+        .filter_map(|(i, (competition, alias))| {
+            Some((i, *competition, dataset.crew(*competition, alias)?))
+        })
+        .collect();
+
+    let rows = crews
+        .iter()
+        .flat_map(|(_, _, crew)| {
+            blocks
+                .iter()
+                .filter_map(move |(year, _, _)| crew.year(*year)?.iter().max())
+        })
+        .max()
+        .copied()
+        .unwrap_or(0) as f64;
+
+    if blocks.is_empty() || rows == 0.0 {
+        return None;
+    }
+
+    let legend_top = MARGIN + 32.0;
+    let top = legend_top + series.len() as f64 * LEGEND_ROW_HEIGHT + 8.0;
+    let bottom = top + rows * HISTORY_ROW_HEIGHT;
+    width += NAME_WIDTH / 2.0;
+    let height = bottom + HEADER_HEIGHT / 2.0 + MARGIN;
+
+    let y = |position: u8| top + (position as f64 - 0.5) * HISTORY_ROW_HEIGHT;
+    let dashes = ["", r#"stroke-dasharray="6 3""#, r#"stroke-dasharray="2 2""#];
+    let dash = |competition: Competition| {
+        let i = series
+            .iter()
+            .position(|(c, _)| *c == competition)
+            .unwrap_or(0);
+        dashes[i % dashes.len()]
+    };
+    let colour = |alias: &str| {
+        dataset
+            .club_of(alias)
+            .map(|club| hex_colour(club.colour))
+            .unwrap_or_else(|| String::from("black"))
+    };
+
+    let mut svg = Svg::new(width, height);
+
+    svg.text(
+        (width / 2.0, MARGIN + 8.0),
+        "middle",
+        "Position by year",
+        r#"font-size="14" font-weight="bold""#,
+    );
+
+    for (i, competition, crew) in &crews {
+        let legend_y = legend_top + *i as f64 * LEGEND_ROW_HEIGHT;
+
+        svg.line(
+            (MARGIN, legend_y),
+            (MARGIN + 32.0, legend_y),
+            &format!(
+                r#"stroke="{}" stroke-width="2" {}"#,
+                colour(&crew.alias),
+                dash(*competition)
+            ),
+        );
+        svg.text(
+            (MARGIN + 40.0, legend_y),
+            "start",
+            &format!("{} ({competition})", crew.name),
+            "",
+        );
+    }
+
+    for (year, x, days) in &blocks {
+        let block_width = *days as f64 * HISTORY_DAY_WIDTH;
+        let chart = series
+            .iter()
+            .find_map(|(competition, _)| dataset.chart(*competition, *year));
+
+        if let Some(chart) = chart {
+            let mut head = 0u32;
+
+            for (i, size) in chart.crews_per_division.iter().enumerate() {
+                if i % 2 == 1 {
+                    svg.rect(
+                        (*x, top + head as f64 * HISTORY_ROW_HEIGHT),
+                        (block_width, *size as f64 * HISTORY_ROW_HEIGHT),
+                        r##"fill="#eeeeee""##,
+                    );
+                }
+
+                head += *size as u32;
+            }
+        }
+
+        svg.line(
+            (*x, top),
+            (*x, bottom),
+            r##"stroke="#cccccc" stroke-width="0.5""##,
+        );
+        svg.text(
+            (*x + block_width / 2.0, bottom + 10.0),
+            "middle",
+            &year.to_string(),
+            r#"font-size="8""#,
+        );
+    }
+
+    for (_, competition, crew) in &crews {
+        let style = format!(
+            r#"stroke="{}" stroke-width="1.5" stroke-linejoin="round" {}"#,
+            colour(&crew.alias),
+            dash(*competition)
+        );
+
+        // Consecutive years are joined up, but gaps (years the crew didn't
+        // compete) break the line.
+        let mut points = vec![];
+        let mut last = None;
+
+        for (year, x, _) in &blocks {
+            let Some(positions) = crew.year(*year) else {
+                if points.len() > 1 {
+                    svg.polyline(&points, &style);
+                }
+
+                points.clear();
+                continue;
+            };
+
+            for (day, position) in positions.iter().enumerate() {
+                if *position != 0 {
+                    let point = (*x + day as f64 * HISTORY_DAY_WIDTH, y(*position));
+
+                    points.push(point);
+                    last = Some(point);
+                }
+            }
+        }
+
+        if points.len() > 1 {
+            svg.polyline(&points, &style);
+        }
+
+        if let Some((x, y)) = last {
+            svg.text((x + 4.0, y), "start", &crew.alias, r#"font-size="8""#);
+        }
+    }
+
+    Some(svg.finish())
+}
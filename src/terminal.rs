@@ -0,0 +1,246 @@
+// Renders bumps charts as text for the terminal, using box-drawing characters.
+//
+// Each position gets a row (with a separator row between divisions), and each
+// day a few columns. A crew's line runs along the row of its start position,
+// turns halfway through the day, and carries on along the row of its finish
+// position. Where lines meet in a cell, the cell shows their union, so a bump
+// looks like
+//
+// ───┬───
+// ───┴───
+
+use std::collections::HashSet;
+use std::fmt::Write;
+
+use crate::competition::Competition;
+use crate::dataset::Dataset;
+
+const DAY_WIDTH: usize = 7;
+const NAME_WIDTH: usize = 20;
+
+const NORTH: u8 = 1;
+const SOUTH: u8 = 2;
+const EAST: u8 = 4;
+const WEST: u8 = 8;
+
+// Indexed by the union of the directions a cell's lines leave in.
+const BOX_CHARS: [char; 16] = [
+    ' ', '│', '│', '│', '─', '╰', '╭', '├', '─', '╯', '╮', '┤', '─', '┴', '┬', '┼',
+];
+
+#[derive(Debug, Default, Clone)]
+pub struct Options {
+    /// Colour each crew's line with its club's colour, using 24-bit ANSI
+    /// escape codes.
+    pub truecolor: bool,
+    /// Crew aliases or club names to pick out.
+    pub highlight: Vec<String>,
+}
+
+#[derive(Clone, Copy, Default)]
+struct Cell {
+    directions: u8,
+    colour: Option<(u8, u8, u8)>,
+    highlighted: bool,
+}
+
+/// Draw the bumps chart for `competition` in `year`, or `None` if there's no
+/// data for that year.
+pub fn bumps_chart(
+    dataset: &Dataset,
+    competition: Competition,
+    year: u32,
+    options: &Options,
+) -> Option<String> {
+    let chart = dataset.chart(competition, year)?;
+    let divisions = chart.divisions();
+    let crews: Vec<_> = dataset.crews_in(competition, year).collect();
+
+    let positions = crews
+        .iter()
+        .filter_map(|crew| crew.year(year)?.iter().max())
+        .max()
+        .copied()?;
+
+    // Divisions past the end of DIVS: are treated as part of the last one.
+    let separators_above = |position: u8| {
+        divisions
+            .locate(position)
+            .map(|place| place.division as usize - 1)
+            .unwrap_or(chart.crews_per_division.len().saturating_sub(1))
+    };
+    let row = |position: u8| position as usize - 1 + separators_above(position);
+
+    let rows = row(positions) + 1;
+    let columns = chart.days as usize * DAY_WIDTH;
+    let mut grid = vec![vec![Cell::default(); columns]; rows];
+
+    let highlighted: HashSet<&str> = crews
+        .iter()
+        .filter(|crew| {
+            options.highlight.iter().any(|x| {
+                *x == crew.alias || dataset.club_of(&crew.alias).map(|club| &club.name) == Some(x)
+            })
+        })
+        .map(|crew| crew.alias.as_str())
+        .collect();
+
+    // Draw highlighted crews last, so they win any shared cells.
+    let mut order = crews.clone();
+    order.sort_by_key(|crew| highlighted.contains(crew.alias.as_str()));
+
+    for crew in &order {
+        let Some(positions) = crew.year(year) else {
+            continue;
+        };
+
+        let colour = dataset.club_of(&crew.alias).map(|club| club.colour);
+        let highlighted = highlighted.contains(crew.alias.as_str());
+
+        let mut paint = |row: usize, column: usize, directions: u8| {
+            let cell = &mut grid[row][column];
+            cell.directions |= directions;
+            cell.colour = colour;
+            cell.highlighted = highlighted;
+        };
+
+        for (day, pair) in positions.windows(2).enumerate() {
+            let (start, finish) = (pair[0], pair[1]);
+
+            if start == 0 || finish == 0 {
+                continue;
+            }
+
+            let (from, to) = (row(start), row(finish));
+            let left = day * DAY_WIDTH;
+            let turn = left + DAY_WIDTH / 2;
+
+            for column in left..turn {
+                paint(from, column, EAST | WEST);
+            }
+
+            for column in (turn + 1)..(left + DAY_WIDTH) {
+                paint(to, column, EAST | WEST);
+            }
+
+            if from == to {
+                paint(from, turn, EAST | WEST);
+            } else {
+                let (up, down) = if to < from {
+                    (NORTH, SOUTH)
+                } else {
+                    (SOUTH, NORTH)
+                };
+
+                paint(from, turn, WEST | up);
+                paint(to, turn, EAST | down);
+
+                for middle in from.min(to) + 1..from.max(to) {
+                    paint(middle, turn, NORTH | SOUTH);
+                }
+            }
+        }
+    }
+
+    let mut names_left = vec![String::new(); rows];
+    let mut names_right = vec![String::new(); rows];
+
+    for crew in &crews {
+        let Some(positions) = crew.year(year) else {
+            continue;
+        };
+
+        let marker = if highlighted.contains(crew.alias.as_str()) && !options.truecolor {
+            "> "
+        } else {
+            ""
+        };
+
+        if let (Some(start), Some(finish)) = (positions.first(), positions.last()) {
+            if *start != 0 {
+                names_left[row(*start)] = format!("{marker}{}", crew.name);
+            }
+
+            if *finish != 0 {
+                names_right[row(*finish)] = format!("{marker}{}", crew.name);
+            }
+        }
+    }
+
+    let style = |cell: &Cell, text: &str| {
+        if !options.truecolor {
+            return String::from(text);
+        }
+
+        let weight = if highlighted.is_empty() {
+            ""
+        } else if cell.highlighted {
+            "\x1b[1m"
+        } else {
+            "\x1b[2m"
+        };
+
+        match cell.colour {
+            Some((r, g, b)) => format!("{weight}\x1b[38;2;{r};{g};{b}m{text}\x1b[0m"),
+            None => format!("{weight}{text}\x1b[0m"),
+        }
+    };
+
+    let truncate = |name: &str| name.chars().take(NAME_WIDTH).collect::<String>();
+
+    let mut out = String::new();
+
+    writeln!(out, "{competition} {year}").unwrap();
+    write!(out, "{:NAME_WIDTH$} ", "").unwrap();
+
+    for day in 1..=chart.days {
+        write!(out, "{:^DAY_WIDTH$}", format!("Day {day}")).unwrap();
+    }
+
+    writeln!(out).unwrap();
+
+    // Rows holding a crew's name are styled like that crew's line.
+    let name_cell = |row: &[Cell], first: bool| {
+        let cell = if first { row.first() } else { row.last() };
+        cell.copied().unwrap_or_default()
+    };
+
+    let separators: HashSet<usize> = chart
+        .crews_per_division
+        .iter()
+        .scan(0usize, |head, size| {
+            *head += *size as usize;
+            Some(*head)
+        })
+        .take(chart.crews_per_division.len().saturating_sub(1))
+        .enumerate()
+        .map(|(i, bottom)| bottom + i)
+        .collect();
+
+    for (i, cells) in grid.iter().enumerate() {
+        let separator = separators.contains(&i);
+
+        let left = truncate(&names_left[i]);
+        write!(
+            out,
+            "{} ",
+            style(&name_cell(cells, true), &format!("{left:>NAME_WIDTH$}"))
+        )
+        .unwrap();
+
+        for cell in cells {
+            match (cell.directions, separator) {
+                (0, true) => out.push('┄'),
+                (0, false) => out.push(' '),
+                (directions, _) => {
+                    out.push_str(&style(cell, &BOX_CHARS[directions as usize].to_string()))
+                }
+            }
+        }
+
+        let right = truncate(&names_right[i]);
+        writeln!(out, " {}", style(&name_cell(cells, false), &right)).unwrap();
+    }
+
+    Some(out)
+}
@@ -1,39 +1,191 @@
-// Different years have different numbers of days, so we need to extract that
-// from data files.
+// Different years have different numbers of days and divisions, so we need to
+// extract that from the chart files (Charts/<Competition>/<year>.dat).
+//
+// File format:
+//
+// DAYS: 4
+// DIVS: 17,17,17,17,16
+// ...
+//
+// Every header is a key ending in a colon, followed by its value.
 
-use std::collections::HashMap;
 use std::io::Read;
-use std::num::ParseIntError;
+use std::path::Path;
 
-pub struct Year {
+use crate::division::Divisions;
+use crate::error::BumpsError;
+
+pub const DAYS: &str = "DAYS";
+pub const DIVS: &str = "DIVS";
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct YearChart {
     pub days: u8,
-    // crews_per_division: Vec<u8>,
+    pub crews_per_division: Vec<u8>,
+    // Every header in the file, in order, including DAYS and DIVS.
+    headers: Vec<(String, String)>,
 }
 
-impl Year {
-    pub fn from_file(file: &mut dyn Read) -> Result<Self, Box<dyn std::error::Error>> {
-        let mut result = String::new();
+impl YearChart {
+    /// A chart with just the DAYS and DIVS headers, e.g. for results that
+    /// didn't come from a chart file.
+    pub fn new(days: u8, crews_per_division: Vec<u8>) -> Self {
+        let divs: Vec<String> = crews_per_division.iter().map(|x| x.to_string()).collect();
+
+        YearChart {
+            days,
+            crews_per_division,
+            headers: vec![
+                (String::from(DAYS), days.to_string()),
+                (String::from(DIVS), divs.join(",")),
+            ],
+        }
+    }
+
+    // `path` is only used for error messages.
+    pub fn from_file(file: &mut dyn Read, path: &Path) -> Result<Self, BumpsError> {
+        let mut contents = String::new();
+
+        file.read_to_string(&mut contents)
+            .map_err(BumpsError::decode(path))?;
 
-        file.read_to_string(&mut result)?;
+        Self::parse(&contents, path)
+    }
 
-        let pairs: HashMap<String, String> = result
+    pub fn parse(contents: &str, path: &Path) -> Result<Self, BumpsError> {
+        let headers: Vec<(String, String)> = contents
             .split('\n')
-            .filter_map(|x| x.trim().split_once(' '))
-            .map(|(x, y)| (String::from(x), String::from(y)))
+            .filter_map(|line| {
+                let line = line.trim();
+                let (key, value) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+                let key = key.strip_suffix(':')?;
+
+                Some((String::from(key), String::from(value.trim())))
+            })
             .collect();
 
-        let days = pairs["DAYS:"].parse::<u8>()?;
-        let crews_per_division: Result<Vec<u8>, ParseIntError> =
-            pairs["DIVS:"].split(',').map(|x| x.parse::<u8>()).collect();
+        let header = |key: &'static str| {
+            headers
+                .iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, v)| v.as_str())
+                .ok_or_else(|| BumpsError::ChartHeader {
+                    path: path.to_path_buf(),
+                    key,
+                    message: String::from("missing"),
+                })
+        };
 
-        let _crews_per_division = match crews_per_division {
-            Ok(crews_per_division) => crews_per_division,
-            Err(err) => return Err(Box::new(err)),
+        let invalid = |key: &'static str, value: &str| {
+            let value = String::from(value);
+            move |err| BumpsError::ChartHeader {
+                path: path.to_path_buf(),
+                key,
+                message: format!("invalid value {value:?}: {err}"),
+            }
         };
 
-        Ok(Year {
+        let days = header(DAYS)?;
+        let days = days.parse::<u8>().map_err(invalid(DAYS, days))?;
+
+        let divs = header(DIVS)?;
+        let crews_per_division = divs
+            .split(',')
+            .map(str::trim)
+            .filter(|x| !x.is_empty())
+            .map(|x| x.parse::<u8>())
+            .collect::<Result<Vec<u8>, _>>()
+            .map_err(invalid(DIVS, divs))?;
+
+        Ok(YearChart {
             days,
-            // crews_per_division: crews_per_division,
+            crews_per_division,
+            headers,
         })
     }
+
+    pub fn divisions(&self) -> Divisions {
+        Divisions::new(self.crews_per_division.clone())
+    }
+}
+
+impl YearChart {
+    /// Look up the raw value of any header, e.g. `chart.get("DAYS")`.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn headers(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.headers.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    /// Headers other than the ones parsed into typed fields.
+    pub fn unknown_headers(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.headers().filter(|(k, _)| *k != DAYS && *k != DIVS)
+    }
+
+    /// The total number of crews across all divisions.
+    pub fn crews(&self) -> u32 {
+        self.crews_per_division.iter().map(|x| *x as u32).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use super::{YearChart, DIVS};
+    use crate::division::Divisions;
+    use crate::error::BumpsError;
+
+    fn parse(contents: &str) -> Result<YearChart, BumpsError> {
+        YearChart::parse(contents, Path::new("2000.dat"))
+    }
+
+    #[test]
+    fn headers() {
+        let chart = parse("DAYS: 4\nDIVS: 3, 2,2\nTITLE: Mays 2000\n\nsome crew\n").unwrap();
+
+        assert_eq!(chart.days, 4);
+        assert_eq!(chart.crews_per_division, [3, 2, 2]);
+        assert_eq!(chart.crews(), 7);
+        assert_eq!(chart.get(DIVS), Some("3, 2,2"));
+        assert_eq!(
+            chart.unknown_headers().collect::<Vec<_>>(),
+            [("TITLE", "Mays 2000")]
+        );
+        assert_eq!(chart.divisions(), Divisions::new(vec![3, 2, 2]));
+    }
+
+    #[test]
+    fn missing_days() {
+        let err = parse("DIVS: 3,2\n").unwrap_err();
+
+        assert!(matches!(
+            err,
+            BumpsError::ChartHeader { key: "DAYS", ref message, .. } if message == "missing"
+        ));
+    }
+
+    #[test]
+    fn invalid_divs() {
+        let err = parse("DAYS: 4\nDIVS: 3,two\n").unwrap_err();
+
+        assert!(matches!(err, BumpsError::ChartHeader { key: "DIVS", .. }));
+        assert_eq!(
+            err.to_string(),
+            "2000.dat: DIVS header: invalid value \"3,two\": invalid digit found in string"
+        );
+    }
+
+    #[test]
+    fn new_matches_parse() {
+        assert_eq!(
+            parse("DAYS: 4\nDIVS: 3,2\n").unwrap(),
+            YearChart::new(4, vec![3, 2])
+        );
+    }
 }